@@ -0,0 +1,33 @@
+#![allow(clippy::needless_return)]
+use merkletreelib::MerkleTree;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+fn bench<F: FnMut() -> MerkleTree>(name: &str, iterations: u32, mut build: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(build());
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!("{:<24} {:>12.3?} per build", name, per_iteration);
+    return per_iteration;
+}
+
+fn main() {
+    for count in [10_000u64, 100_000, 1_000_000] {
+        let values: Vec<[u8; 8]> = (0..count).map(|i| i.to_le_bytes()).collect();
+        let iterations = (1_000_000 / count).max(1) as u32 * 3;
+        println!("{} leaves", count);
+        let serial = bench("from_bytes", iterations, || {
+            MerkleTree::from_bytes(black_box(&values))
+        });
+        let parallel = bench("from_bytes_par", iterations, || {
+            MerkleTree::from_bytes_par(black_box(&values))
+        });
+        println!(
+            "{:<24} {:>11.2}x",
+            "speedup",
+            serial.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
+}
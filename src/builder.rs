@@ -0,0 +1,108 @@
+use crate::hash::{Hasher, Sha256Hasher};
+use crate::tree::{PaddingStrategy, parent_hash};
+use alloc::vec::Vec;
+
+pub struct TreeBuilder<H: Hasher<N> = Sha256Hasher, const N: usize = 32> {
+    current: Vec<[u8; N]>,
+    next: Vec<[u8; N]>,
+    hasher: H,
+    padding: PaddingStrategy,
+}
+
+impl TreeBuilder {
+    pub fn new() -> Self {
+        return TreeBuilder::new_with(Sha256Hasher);
+    }
+}
+
+impl<H: Hasher<N>, const N: usize> TreeBuilder<H, N> {
+    pub fn new_with(hasher: H) -> Self {
+        return TreeBuilder {
+            current: Vec::new(),
+            next: Vec::new(),
+            hasher,
+            padding: PaddingStrategy::Duplicate,
+        };
+    }
+    pub fn with_padding_strategy(mut self, padding: PaddingStrategy) -> Self {
+        self.padding = padding;
+        return self;
+    }
+    pub fn build(&mut self, leaves: &[[u8; N]]) -> Option<[u8; N]> {
+        if leaves.is_empty() {
+            return None;
+        }
+        self.current.clear();
+        self.current.extend_from_slice(leaves);
+        while self.current.len() > 1 {
+            self.next.clear();
+            for pair in self.current.chunks(2) {
+                self.next
+                    .push(parent_hash(pair, &self.hasher, self.padding));
+            }
+            core::mem::swap(&mut self.current, &mut self.next);
+        }
+        return Some(self.current[0]);
+    }
+}
+
+impl Default for TreeBuilder {
+    fn default() -> Self {
+        return TreeBuilder::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MerkleTree;
+    use crate::hash::sha256;
+    mod reusable_builder {
+        use super::*;
+        #[test]
+        fn reused_builder_matches_from_bytes() {
+            let mut builder = TreeBuilder::new();
+            for size in 0..100u8 {
+                let values: Vec<[u8; 1]> = (0..size % 17).map(|i| [i.wrapping_add(size)]).collect();
+                let leaves: Vec<[u8; 32]> = values.iter().map(|v| sha256(v)).collect();
+                let tree = MerkleTree::from_bytes(&values);
+                assert_eq!(builder.build(&leaves).as_ref(), tree.get_root());
+            }
+        }
+        #[test]
+        fn custom_hasher_matches_from_bytes_with() {
+            struct TruncatedSha256;
+            impl Hasher<16> for TruncatedSha256 {
+                fn digest(&self, data: &[u8]) -> [u8; 16] {
+                    return sha256(data)[..16].try_into().unwrap();
+                }
+            }
+            let values = ["a", "b", "c", "d", "e"];
+            let tree = MerkleTree::from_bytes_with(&values, TruncatedSha256);
+            let mut builder = TreeBuilder::new_with(TruncatedSha256);
+            assert_eq!(builder.build(tree.leaves()).as_ref(), tree.get_root());
+        }
+        #[test]
+        fn promote_padding_matches_tree_with_promote() {
+            let mut builder = TreeBuilder::new().with_padding_strategy(PaddingStrategy::Promote);
+            for size in 1..20u8 {
+                let values: Vec<[u8; 1]> = (0..size).map(|i| [i]).collect();
+                let tree =
+                    MerkleTree::from_bytes(&values).with_padding_strategy(PaddingStrategy::Promote);
+                assert_eq!(builder.build(tree.leaves()).as_ref(), tree.get_root());
+            }
+        }
+        #[test]
+        fn builder_keeps_buffers_between_builds() {
+            let mut builder = TreeBuilder::new();
+            let leaves: Vec<[u8; 32]> = (0..64u8).map(|i| sha256(&[i])).collect();
+            builder.build(&leaves);
+            let capacity = builder.current.capacity() + builder.next.capacity();
+            builder.build(&leaves[..40]);
+            assert_eq!(
+                builder.current.capacity() + builder.next.capacity(),
+                capacity
+            );
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::tree::{MerkleTree, PaddingStrategy, hash_pair};
+use alloc::vec::Vec;
+
+impl MerkleTree {
+    pub fn consistency_proof(&self, old_size: usize) -> Option<Vec<[u8; 32]>> {
+        if self.padding != PaddingStrategy::Promote || old_size == 0 || old_size > self.leaves.len()
+        {
+            return None;
+        }
+        let mut proof = Vec::new();
+        subproof(old_size, &self.leaves, true, &mut proof);
+        return Some(proof);
+    }
+}
+
+pub fn verify_consistency(
+    old_root: [u8; 32],
+    new_root: [u8; 32],
+    old_size: usize,
+    new_size: usize,
+    proof: &[[u8; 32]],
+) -> bool {
+    if old_size == 0 || old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+    let mut path = Vec::with_capacity(proof.len() + 1);
+    if old_size.is_power_of_two() {
+        path.push(old_root);
+    }
+    path.extend_from_slice(proof);
+    let Some((first, rest)) = path.split_first() else {
+        return false;
+    };
+    let mut old_node = old_size - 1;
+    let mut new_node = new_size - 1;
+    while old_node & 1 == 1 {
+        old_node >>= 1;
+        new_node >>= 1;
+    }
+    let mut old_hash = *first;
+    let mut new_hash = *first;
+    for node in rest {
+        if new_node == 0 {
+            return false;
+        }
+        if old_node & 1 == 1 || old_node == new_node {
+            old_hash = hash_pair(node, &old_hash);
+            new_hash = hash_pair(node, &new_hash);
+            while old_node & 1 == 0 && old_node != 0 {
+                old_node >>= 1;
+                new_node >>= 1;
+            }
+        } else {
+            new_hash = hash_pair(&new_hash, node);
+        }
+        old_node >>= 1;
+        new_node >>= 1;
+    }
+    return new_node == 0 && old_hash == old_root && new_hash == new_root;
+}
+
+fn subproof(old_size: usize, leaves: &[[u8; 32]], complete: bool, proof: &mut Vec<[u8; 32]>) {
+    if old_size == leaves.len() {
+        if !complete {
+            proof.push(subtree_root(leaves));
+        }
+        return;
+    }
+    let split = split_point(leaves.len());
+    if old_size <= split {
+        subproof(old_size, &leaves[..split], complete, proof);
+        proof.push(subtree_root(&leaves[split..]));
+    } else {
+        subproof(old_size - split, &leaves[split..], false, proof);
+        proof.push(subtree_root(&leaves[..split]));
+    }
+}
+
+fn subtree_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.len() == 1 {
+        return leaves[0];
+    }
+    let split = split_point(leaves.len());
+    return hash_pair(
+        &subtree_root(&leaves[..split]),
+        &subtree_root(&leaves[split..]),
+    );
+}
+
+fn split_point(len: usize) -> usize {
+    return 1 << (len - 1).ilog2();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256;
+    fn promote_tree(size: usize) -> MerkleTree {
+        let values: Vec<[u8; 8]> = (0..size as u64).map(|i| i.to_le_bytes()).collect();
+        return MerkleTree::from_bytes_with_padding(&values, PaddingStrategy::Promote);
+    }
+    mod proofs {
+        use super::*;
+        #[test]
+        fn subtree_decomposition_matches_promoted_root() {
+            for size in 1..=17 {
+                let tree = promote_tree(size);
+                assert_eq!(subtree_root(tree.leaves()), *tree.get_root().unwrap());
+            }
+        }
+        #[test]
+        fn every_prefix_of_small_trees_is_consistent() {
+            for new_size in 1..=12 {
+                let new_tree = promote_tree(new_size);
+                let new_root = *new_tree.get_root().unwrap();
+                for old_size in 1..=new_size {
+                    let old_root = *promote_tree(old_size).get_root().unwrap();
+                    let proof = new_tree.consistency_proof(old_size).unwrap();
+                    assert!(
+                        verify_consistency(old_root, new_root, old_size, new_size, &proof),
+                        "{old_size} -> {new_size}"
+                    );
+                }
+            }
+        }
+        #[test]
+        fn power_of_two_old_size_omits_old_root() {
+            let tree = promote_tree(7);
+            let proof = tree.consistency_proof(4).unwrap();
+            assert_eq!(proof, vec![subtree_root(&tree.leaves()[4..])]);
+            let old_root = *promote_tree(4).get_root().unwrap();
+            assert!(verify_consistency(
+                old_root,
+                *tree.get_root().unwrap(),
+                4,
+                7,
+                &proof
+            ));
+        }
+        #[test]
+        fn non_power_of_two_old_size_includes_its_subtrees() {
+            let tree = promote_tree(7);
+            let leaves = tree.leaves();
+            let proof = tree.consistency_proof(3).unwrap();
+            assert_eq!(
+                proof,
+                vec![
+                    leaves[2],
+                    leaves[3],
+                    hash_pair(&leaves[0], &leaves[1]),
+                    subtree_root(&leaves[4..]),
+                ]
+            );
+            let old_root = *promote_tree(3).get_root().unwrap();
+            assert!(verify_consistency(
+                old_root,
+                *tree.get_root().unwrap(),
+                3,
+                7,
+                &proof
+            ));
+        }
+        #[test]
+        fn same_size_needs_no_nodes() {
+            let tree = promote_tree(5);
+            let root = *tree.get_root().unwrap();
+            assert_eq!(tree.consistency_proof(5), Some(vec![]));
+            assert!(verify_consistency(root, root, 5, 5, &[]));
+            assert!(!verify_consistency(root, sha256(b"x"), 5, 5, &[]));
+        }
+        #[test]
+        fn unsupported_requests_have_no_proof() {
+            let tree = promote_tree(5);
+            assert_eq!(tree.consistency_proof(0), None);
+            assert_eq!(tree.consistency_proof(6), None);
+            let duplicate = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert_eq!(duplicate.consistency_proof(2), None);
+        }
+    }
+    mod verification {
+        use super::*;
+        #[test]
+        fn tampered_proofs_are_rejected() {
+            let new_tree = promote_tree(10);
+            let new_root = *new_tree.get_root().unwrap();
+            let old_root = *promote_tree(6).get_root().unwrap();
+            let proof = new_tree.consistency_proof(6).unwrap();
+            assert!(verify_consistency(old_root, new_root, 6, 10, &proof));
+            for i in 0..proof.len() {
+                let mut tampered = proof.clone();
+                tampered[i][0] ^= 1;
+                assert!(!verify_consistency(old_root, new_root, 6, 10, &tampered));
+            }
+            assert!(!verify_consistency(old_root, new_root, 6, 10, &proof[1..]));
+            let mut extended = proof.clone();
+            extended.push(sha256(b"x"));
+            assert!(!verify_consistency(old_root, new_root, 6, 10, &extended));
+        }
+        #[test]
+        fn wrong_sizes_or_roots_are_rejected() {
+            let new_tree = promote_tree(8);
+            let new_root = *new_tree.get_root().unwrap();
+            let old_root = *promote_tree(5).get_root().unwrap();
+            let proof = new_tree.consistency_proof(5).unwrap();
+            assert!(!verify_consistency(old_root, new_root, 5, 9, &proof));
+            assert!(!verify_consistency(old_root, new_root, 6, 8, &proof));
+            assert!(!verify_consistency(new_root, new_root, 5, 8, &proof));
+            assert!(!verify_consistency(old_root, old_root, 5, 8, &proof));
+            assert!(!verify_consistency(old_root, new_root, 0, 8, &proof));
+            assert!(!verify_consistency(new_root, old_root, 8, 5, &proof));
+        }
+    }
+}
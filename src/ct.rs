@@ -0,0 +1,176 @@
+use crate::error::CtParseError;
+use crate::hash::sha256;
+use alloc::vec::Vec;
+
+pub fn ct_leaf_hash(data: &[u8]) -> [u8; 32] {
+    let mut input = Vec::with_capacity(data.len() + 1);
+    input.push(0x00);
+    input.extend_from_slice(data);
+    return sha256(&input);
+}
+
+pub fn parse_ct_audit_proof(bytes: &[u8]) -> Result<Vec<[u8; 32]>, CtParseError> {
+    if bytes.len() < 2 {
+        return Err(CtParseError::Truncated);
+    }
+    let total = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+    let body = bytes.get(2..2 + total).ok_or(CtParseError::Truncated)?;
+    if bytes.len() > 2 + total {
+        return Err(CtParseError::TrailingBytes {
+            count: bytes.len() - 2 - total,
+        });
+    }
+    let mut nodes = Vec::with_capacity(total / 33);
+    let mut offset = 0;
+    while offset < body.len() {
+        let len = body[offset];
+        if len != 32 {
+            return Err(CtParseError::InvalidNodeLength { len });
+        }
+        let node = body
+            .get(offset + 1..offset + 33)
+            .ok_or(CtParseError::Truncated)?;
+        nodes.push(node.try_into().unwrap());
+        offset += 33;
+    }
+    return Ok(nodes);
+}
+
+pub fn verify_ct_inclusion(
+    leaf_hash: [u8; 32],
+    leaf_index: u64,
+    tree_size: u64,
+    nodes: &[[u8; 32]],
+    root: [u8; 32],
+) -> bool {
+    if leaf_index >= tree_size {
+        return false;
+    }
+    let mut index = leaf_index;
+    let mut last_index = tree_size - 1;
+    let mut current = leaf_hash;
+    for node in nodes {
+        if last_index == 0 {
+            return false;
+        }
+        if index & 1 == 1 || index == last_index {
+            current = ct_node_hash(node, &current);
+            while index & 1 == 0 && index != 0 {
+                index >>= 1;
+                last_index >>= 1;
+            }
+        } else {
+            current = ct_node_hash(&current, node);
+        }
+        index >>= 1;
+        last_index >>= 1;
+    }
+    return last_index == 0 && current == root;
+}
+
+fn ct_node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(1 + left.len() + right.len());
+    data.push(0x01);
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    return sha256(&data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn hex32(hex: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        return out;
+    }
+    fn encode(nodes: &[[u8; 32]]) -> Vec<u8> {
+        let mut bytes = ((nodes.len() * 33) as u16).to_be_bytes().to_vec();
+        for node in nodes {
+            bytes.push(32);
+            bytes.extend_from_slice(node);
+        }
+        return bytes;
+    }
+    // Test vectors published with the RFC 6962 reference implementation.
+    const LEAVES: [&[u8]; 8] = [
+        &[],
+        &[0x00],
+        &[0x10],
+        &[0x20, 0x21],
+        &[0x30, 0x31],
+        &[0x40, 0x41, 0x42, 0x43],
+        &[0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57],
+        &[
+            0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6a, 0x6b, 0x6c, 0x6d,
+            0x6e, 0x6f,
+        ],
+    ];
+    const ROOT_8: &str = "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328";
+    mod inclusion {
+        use super::*;
+        #[test]
+        fn published_inclusion_proof_for_leaf_0_verifies() {
+            let nodes = [
+                hex32("96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"),
+                hex32("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                hex32("6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"),
+            ];
+            let parsed = parse_ct_audit_proof(&encode(&nodes)).unwrap();
+            let leaf_hash = ct_leaf_hash(LEAVES[0]);
+            assert!(verify_ct_inclusion(leaf_hash, 0, 8, &parsed, hex32(ROOT_8)));
+        }
+        #[test]
+        fn published_inclusion_proof_for_leaf_5_verifies() {
+            let nodes = [
+                hex32("bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"),
+                hex32("ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0"),
+                hex32("d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7"),
+            ];
+            let leaf_hash = ct_leaf_hash(LEAVES[5]);
+            assert!(verify_ct_inclusion(leaf_hash, 5, 8, &nodes, hex32(ROOT_8)));
+            assert!(!verify_ct_inclusion(leaf_hash, 4, 8, &nodes, hex32(ROOT_8)));
+            assert!(!verify_ct_inclusion(leaf_hash, 5, 6, &nodes, hex32(ROOT_8)));
+        }
+        #[test]
+        fn single_leaf_tree_needs_no_nodes() {
+            let leaf_hash = ct_leaf_hash(LEAVES[0]);
+            assert!(verify_ct_inclusion(leaf_hash, 0, 1, &[], leaf_hash));
+            assert!(!verify_ct_inclusion(leaf_hash, 1, 1, &[], leaf_hash));
+        }
+    }
+    mod parsing {
+        use super::*;
+        #[test]
+        fn empty_proof_parses() {
+            assert_eq!(parse_ct_audit_proof(&[0, 0]), Ok(vec![]));
+        }
+        #[test]
+        fn truncated_input_is_rejected() {
+            let mut bytes = encode(&[[7u8; 32]]);
+            bytes.pop();
+            assert_eq!(parse_ct_audit_proof(&bytes), Err(CtParseError::Truncated));
+            assert_eq!(parse_ct_audit_proof(&[0]), Err(CtParseError::Truncated));
+        }
+        #[test]
+        fn invalid_node_length_is_rejected() {
+            let mut bytes = encode(&[[7u8; 32]]);
+            bytes[2] = 31;
+            assert_eq!(
+                parse_ct_audit_proof(&bytes),
+                Err(CtParseError::InvalidNodeLength { len: 31 })
+            );
+        }
+        #[test]
+        fn trailing_bytes_are_rejected() {
+            let mut bytes = encode(&[[7u8; 32]]);
+            bytes.push(0);
+            assert_eq!(
+                parse_ct_audit_proof(&bytes),
+                Err(CtParseError::TrailingBytes { count: 1 })
+            );
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthError {
+    Leaf { len: usize },
+    Root { len: usize },
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            LengthError::Leaf { len } => {
+                write!(f, "leaf hash must be 32 bytes long, got {} bytes", len)
+            }
+            LengthError::Root { len } => {
+                write!(f, "root hash must be 32 bytes long, got {} bytes", len)
+            }
+        };
+    }
+}
+
+impl core::error::Error for LengthError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CtParseError {
+    Truncated,
+    InvalidNodeLength { len: u8 },
+    TrailingBytes { count: usize },
+}
+
+impl fmt::Display for CtParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CtParseError::Truncated => write!(f, "audit proof is truncated"),
+            CtParseError::InvalidNodeLength { len } => {
+                write!(
+                    f,
+                    "audit proof node must be 32 bytes long, got {} bytes",
+                    len
+                )
+            }
+            CtParseError::TrailingBytes { count } => {
+                write!(f, "audit proof has {} unexpected trailing bytes", count)
+            }
+        };
+    }
+}
+
+impl core::error::Error for CtParseError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    Truncated,
+    InvalidDirection { byte: u8 },
+    TrailingBytes { count: usize },
+    InvalidRlp,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            DecodeError::Truncated => write!(f, "encoded proof is truncated"),
+            DecodeError::InvalidDirection { byte } => {
+                write!(f, "invalid direction byte {:#04x}", byte)
+            }
+            DecodeError::TrailingBytes { count } => {
+                write!(f, "encoded proof has {} unexpected trailing bytes", count)
+            }
+            DecodeError::InvalidRlp => write!(f, "encoded proof is not a canonical RLP proof"),
+        };
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    TooDeep { leaf_count: usize, depth: u32 },
+    DepthOverflow { depth: u32 },
+    InvalidArity { arity: usize },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            BuildError::TooDeep { leaf_count, depth } => {
+                write!(
+                    f,
+                    "{} leaves do not fit in a tree of depth {}",
+                    leaf_count, depth
+                )
+            }
+            BuildError::DepthOverflow { depth } => {
+                write!(f, "a tree of depth {} cannot be addressed", depth)
+            }
+            BuildError::InvalidArity { arity } => {
+                write!(
+                    f,
+                    "a tree needs at least 2 children per node, got {}",
+                    arity
+                )
+            }
+        };
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexError {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f,
+            "leaf index {} is out of range for a tree of {} leaves",
+            self.index, self.len
+        );
+    }
+}
+
+impl core::error::Error for IndexError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    InvalidLength { len: usize },
+    InvalidDigit { index: usize },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            HexError::InvalidLength { len } => {
+                write!(f, "hex hash must be 64 digits long, got {}", len)
+            }
+            HexError::InvalidDigit { index } => {
+                write!(f, "invalid hex digit at byte {}", index)
+            }
+        };
+    }
+}
+
+impl core::error::Error for HexError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleError {
+    EmptyTree,
+    LeafNotFound,
+    IndexOutOfRange { index: usize, len: usize },
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            MerkleError::EmptyTree => write!(f, "the tree has no leaves"),
+            MerkleError::LeafNotFound => write!(f, "the leaf is not in the tree"),
+            MerkleError::IndexOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "leaf index {} is out of range for a tree of {} leaves",
+                    index, len
+                )
+            }
+        };
+    }
+}
+
+impl core::error::Error for MerkleError {}
@@ -0,0 +1,539 @@
+use crate::hash::sha256;
+use crate::smt::default_hashes;
+use crate::tree::{Direction, MerkleTree, PaddingStrategy, hash_pair};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LightSnapshot {
+    root: Option<[u8; 32]>,
+    size: usize,
+    frontier: Vec<[u8; 32]>,
+    padding: PaddingStrategy,
+}
+
+impl LightSnapshot {
+    pub fn new(size: usize, frontier: Vec<[u8; 32]>) -> Option<Self> {
+        if frontier.len() != size.count_ones() as usize {
+            return None;
+        }
+        return Some(LightSnapshot {
+            root: frontier_root(&frontier, size, PaddingStrategy::Duplicate),
+            size,
+            frontier,
+            padding: PaddingStrategy::Duplicate,
+        });
+    }
+    pub fn with_padding_strategy(mut self, padding: PaddingStrategy) -> Self {
+        if self.padding != padding {
+            self.padding = padding;
+            self.root = frontier_root(&self.frontier, self.size, padding);
+        }
+        return self;
+    }
+    pub fn root(&self) -> Option<[u8; 32]> {
+        return self.root;
+    }
+    pub fn size(&self) -> usize {
+        return self.size;
+    }
+    pub fn frontier(&self) -> &[[u8; 32]] {
+        return &self.frontier;
+    }
+    pub fn padding_strategy(&self) -> PaddingStrategy {
+        return self.padding;
+    }
+    pub fn append(&mut self, leaf_hash: [u8; 32]) -> [u8; 32] {
+        frontier_push(&mut self.frontier, self.size, leaf_hash);
+        self.size += 1;
+        let root = frontier_root(&self.frontier, self.size, self.padding)
+            .expect("frontier matches its size");
+        self.root = Some(root);
+        return root;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WindowedTree {
+    window: usize,
+    settled: usize,
+    frontier: Vec<[u8; 32]>,
+    recent: VecDeque<[u8; 32]>,
+}
+
+impl WindowedTree {
+    pub fn new(window: usize) -> Self {
+        return WindowedTree {
+            window,
+            settled: 0,
+            frontier: Vec::new(),
+            recent: VecDeque::new(),
+        };
+    }
+    pub fn window(&self) -> usize {
+        return self.window;
+    }
+    pub fn len(&self) -> usize {
+        return self.settled + self.recent.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+    pub fn push(&mut self, value: &[u8]) {
+        return self.push_leaf_hash(sha256(value));
+    }
+    pub fn push_leaf_hash(&mut self, leaf_hash: [u8; 32]) {
+        self.recent.push_back(leaf_hash);
+        if self.recent.len() > self.window {
+            let oldest = self.recent.pop_front().expect("window holds a leaf");
+            frontier_push(&mut self.frontier, self.settled, oldest);
+            self.settled += 1;
+        }
+    }
+    pub fn get_root(&self) -> Option<[u8; 32]> {
+        if self.is_empty() {
+            return None;
+        }
+        return Some(self.node(self.levels() - 1, 0));
+    }
+    pub fn generate_proof_by_index(&self, index: usize) -> Option<Vec<([u8; 32], Direction)>> {
+        if index < self.settled || index >= self.len() {
+            return None;
+        }
+        let mut proof = Vec::with_capacity(self.levels() - 1);
+        for level in 0..self.levels() - 1 {
+            let position = index >> level;
+            if position % 2 == 1 {
+                proof.push((self.node(level, position - 1), Direction::Left));
+            } else if position + 1 < self.level_len(level) {
+                proof.push((self.node(level, position + 1), Direction::Right));
+            } else {
+                proof.push((self.node(level, position), Direction::Right));
+            }
+        }
+        return Some(proof);
+    }
+    fn levels(&self) -> usize {
+        return (usize::BITS - (self.len() - 1).leading_zeros()) as usize + 1;
+    }
+    fn level_len(&self, level: usize) -> usize {
+        return self.len().div_ceil(1 << level);
+    }
+    fn node(&self, level: usize, position: usize) -> [u8; 32] {
+        if (position + 1) << level <= self.settled {
+            let above = (self.settled >> level >> 1).count_ones() as usize;
+            return self.frontier[above];
+        }
+        if level == 0 {
+            return self.recent[position - self.settled];
+        }
+        let left = self.node(level - 1, 2 * position);
+        let right = if 2 * position + 1 < self.level_len(level - 1) {
+            self.node(level - 1, 2 * position + 1)
+        } else {
+            left
+        };
+        return hash_pair(&left, &right);
+    }
+}
+
+impl MerkleTree {
+    pub fn light_snapshot(&self) -> Option<LightSnapshot> {
+        if self.fixed_depth.is_some() {
+            return None;
+        }
+        return Some(LightSnapshot {
+            root: self.get_root().copied(),
+            size: self.leaves.len(),
+            frontier: self.frontier(),
+            padding: self.padding,
+        });
+    }
+    pub fn frontier(&self) -> Vec<[u8; 32]> {
+        let mut frontier = Vec::new();
+        for (size, leaf) in self.leaves.iter().enumerate() {
+            frontier_push(&mut frontier, size, *leaf);
+        }
+        return frontier;
+    }
+    pub fn verify_append_claim(&self, value: &[u8], claimed_root: [u8; 32]) -> bool {
+        let mut frontier = self.frontier();
+        frontier_push(&mut frontier, self.leaves.len(), sha256(value));
+        let size = self.leaves.len() + 1;
+        let root = match self.fixed_depth {
+            Some(depth) => fixed_frontier_root(&frontier, size, &default_hashes(depth as usize)),
+            None => frontier_root(&frontier, size, self.padding),
+        };
+        return root == Some(claimed_root);
+    }
+    pub fn root_matches_any_prefix(&self, claimed_root: [u8; 32]) -> Option<usize> {
+        let zeros = self.fixed_depth.map(|depth| default_hashes(depth as usize));
+        let mut frontier = Vec::new();
+        for (size, leaf) in self.leaves.iter().enumerate() {
+            frontier_push(&mut frontier, size, *leaf);
+            let root = match &zeros {
+                Some(zeros) => fixed_frontier_root(&frontier, size + 1, zeros),
+                None => frontier_root(&frontier, size + 1, self.padding),
+            };
+            if root? == claimed_root {
+                return Some(size + 1);
+            }
+        }
+        return None;
+    }
+}
+
+pub fn extend_root(
+    old_root: [u8; 32],
+    old_size: usize,
+    new_leaves: &[[u8; 32]],
+    consistency_proof: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if frontier_root(consistency_proof, old_size, PaddingStrategy::Duplicate)? != old_root {
+        return None;
+    }
+    let mut frontier = consistency_proof.to_vec();
+    for (offset, leaf) in new_leaves.iter().enumerate() {
+        frontier_push(&mut frontier, old_size + offset, *leaf);
+    }
+    return frontier_root(
+        &frontier,
+        old_size + new_leaves.len(),
+        PaddingStrategy::Duplicate,
+    );
+}
+
+pub(crate) fn frontier_push(frontier: &mut Vec<[u8; 32]>, size: usize, leaf: [u8; 32]) {
+    let mut node = leaf;
+    let mut remaining = size;
+    while remaining & 1 == 1 {
+        let left = frontier.pop().expect("frontier does not match its size");
+        node = hash_pair(&left, &node);
+        remaining >>= 1;
+    }
+    frontier.push(node);
+}
+
+pub(crate) fn frontier_root(
+    frontier: &[[u8; 32]],
+    size: usize,
+    padding: PaddingStrategy,
+) -> Option<[u8; 32]> {
+    if size == 0 || frontier.len() != size.count_ones() as usize {
+        return None;
+    }
+    let mut blocks = frontier.iter().rev();
+    let mut current = *blocks.next()?;
+    if padding == PaddingStrategy::Promote {
+        for block in blocks {
+            current = hash_pair(block, &current);
+        }
+        return Some(current);
+    }
+    let mut level_size = size >> size.trailing_zeros();
+    let mut position = level_size - 1;
+    while level_size > 1 {
+        if position % 2 == 1 {
+            current = hash_pair(blocks.next()?, &current);
+        } else {
+            current = hash_pair(&current, &current);
+        }
+        position /= 2;
+        level_size = level_size.div_ceil(2);
+    }
+    return Some(current);
+}
+
+fn fixed_frontier_root(frontier: &[[u8; 32]], size: usize, zeros: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let depth = zeros.len() - 1;
+    if size > 1usize.checked_shl(depth as u32)? || frontier.len() != size.count_ones() as usize {
+        return None;
+    }
+    let mut blocks = frontier.iter().rev();
+    let mut partial: Option<[u8; 32]> = None;
+    for (level, zero) in zeros[..depth].iter().enumerate() {
+        partial = match ((size >> level) & 1 == 1, partial) {
+            (true, right) => Some(hash_pair(blocks.next()?, &right.unwrap_or(*zero))),
+            (false, Some(left)) => Some(hash_pair(&left, zero)),
+            (false, None) => None,
+        };
+    }
+    return Some(match partial {
+        Some(root) => root,
+        None => blocks.next().copied().unwrap_or(zeros[depth]),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    mod root_extension {
+        use super::*;
+        #[test]
+        fn extend_three_leaf_root_by_two_leaves() {
+            let old = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let new_leaves = [sha256("d".as_bytes()), sha256("e".as_bytes())];
+            let extended = extend_root(*old.get_root().unwrap(), 3, &new_leaves, &old.frontier());
+            let full = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            assert_eq!(extended.as_ref(), full.get_root());
+        }
+        #[test]
+        fn extension_matches_rebuild_for_many_sizes() {
+            let values: Vec<[u8; 1]> = (0..20u8).map(|i| [i]).collect();
+            let leaves: Vec<[u8; 32]> = values.iter().map(|v| sha256(v)).collect();
+            for old_size in 1..values.len() {
+                let old = MerkleTree::from_bytes(&values[..old_size]);
+                for new_size in old_size..=values.len() {
+                    let extended = extend_root(
+                        *old.get_root().unwrap(),
+                        old_size,
+                        &leaves[old_size..new_size],
+                        &old.frontier(),
+                    );
+                    let full = MerkleTree::from_bytes(&values[..new_size]);
+                    assert_eq!(extended.as_ref(), full.get_root());
+                }
+            }
+        }
+        #[test]
+        fn wrong_old_root_or_proof_is_rejected() {
+            let old = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let new_leaves = [sha256("d".as_bytes())];
+            let root = *old.get_root().unwrap();
+            let mut frontier = old.frontier();
+            assert!(extend_root(sha256(&[]), 3, &new_leaves, &frontier).is_none());
+            assert!(extend_root(root, 4, &new_leaves, &frontier).is_none());
+            frontier[0][0] ^= 1;
+            assert!(extend_root(root, 3, &new_leaves, &frontier).is_none());
+        }
+    }
+    mod prefix_roots {
+        use super::*;
+        #[test]
+        fn root_of_five_leaf_prefix_is_detected() {
+            let values = ["a", "b", "c", "d", "e", "f", "g", "h"];
+            let tree = MerkleTree::from_bytes(&values);
+            let prefix = MerkleTree::from_bytes(&values[..5]);
+            assert_eq!(
+                tree.root_matches_any_prefix(*prefix.get_root().unwrap()),
+                Some(5)
+            );
+            assert_eq!(
+                tree.root_matches_any_prefix(*tree.get_root().unwrap()),
+                Some(8)
+            );
+            assert_eq!(
+                tree.root_matches_any_prefix(sha256("a".as_bytes())),
+                Some(1)
+            );
+        }
+        #[test]
+        fn prefix_roots_of_a_promote_tree_are_detected() {
+            let values = ["a", "b", "c", "d", "e", "f", "g"];
+            let tree = MerkleTree::from_bytes_with_padding(&values, PaddingStrategy::Promote);
+            for size in 1..=values.len() {
+                let prefix =
+                    MerkleTree::from_bytes_with_padding(&values[..size], PaddingStrategy::Promote);
+                assert_eq!(
+                    tree.root_matches_any_prefix(*prefix.get_root().unwrap()),
+                    Some(size)
+                );
+            }
+            let duplicate = MerkleTree::from_bytes(&values[..5]);
+            assert_eq!(
+                tree.root_matches_any_prefix(*duplicate.get_root().unwrap()),
+                None
+            );
+        }
+        #[test]
+        fn prefix_roots_of_a_fixed_depth_tree_are_detected() {
+            let values = ["a", "b", "c", "d", "e"];
+            let tree = MerkleTree::from_bytes_fixed_depth(&values, 3).unwrap();
+            for size in 1..=values.len() {
+                let prefix = MerkleTree::from_bytes_fixed_depth(&values[..size], 3).unwrap();
+                assert_eq!(
+                    tree.root_matches_any_prefix(*prefix.get_root().unwrap()),
+                    Some(size)
+                );
+            }
+            let unfixed = MerkleTree::from_bytes(&values[..3]);
+            assert_eq!(
+                tree.root_matches_any_prefix(*unfixed.get_root().unwrap()),
+                None
+            );
+        }
+        #[test]
+        fn unrelated_root_is_not_detected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let other = MerkleTree::from_bytes(&["a", "b", "x"]);
+            assert_eq!(
+                tree.root_matches_any_prefix(*other.get_root().unwrap()),
+                None
+            );
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(empty.root_matches_any_prefix(sha256(&[])), None);
+        }
+    }
+    mod append_claims {
+        use super::*;
+        #[test]
+        fn correct_claim_passes_and_wrong_claim_fails() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let after = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let claimed = *after.get_root().unwrap();
+            assert!(tree.verify_append_claim("d".as_bytes(), claimed));
+            assert!(!tree.verify_append_claim("e".as_bytes(), claimed));
+            assert!(!tree.verify_append_claim("d".as_bytes(), *tree.get_root().unwrap()));
+            assert_eq!(tree.leaves.len(), 3);
+        }
+        #[test]
+        fn claims_on_promote_trees_use_promoted_roots() {
+            let tree = MerkleTree::from_bytes_with_padding(
+                &["a", "b", "c", "d"],
+                PaddingStrategy::Promote,
+            );
+            let after = MerkleTree::from_bytes_with_padding(
+                &["a", "b", "c", "d", "e"],
+                PaddingStrategy::Promote,
+            );
+            assert!(tree.verify_append_claim(b"e", *after.get_root().unwrap()));
+            let duplicate = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            assert!(!tree.verify_append_claim(b"e", *duplicate.get_root().unwrap()));
+        }
+        #[test]
+        fn claims_on_fixed_depth_trees_use_zero_subtrees() {
+            let tree = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c"], 2).unwrap();
+            let after = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c", "d"], 2).unwrap();
+            assert!(tree.verify_append_claim(b"d", *after.get_root().unwrap()));
+            assert!(!tree.verify_append_claim(b"e", *after.get_root().unwrap()));
+            let empty = MerkleTree::from_bytes_fixed_depth::<&str>(&[], 3).unwrap();
+            let single = MerkleTree::from_bytes_fixed_depth(&["a"], 3).unwrap();
+            assert!(empty.verify_append_claim(b"a", *single.get_root().unwrap()));
+            assert!(!after.verify_append_claim(b"e", *after.get_root().unwrap()));
+        }
+        #[test]
+        fn claim_on_empty_tree_is_single_leaf_root() {
+            let tree = MerkleTree::from_bytes::<&str>(&[]);
+            assert!(tree.verify_append_claim("a".as_bytes(), sha256("a".as_bytes())));
+        }
+    }
+    mod windowed_tree {
+        use super::*;
+        use crate::tree::verify_proof;
+        #[test]
+        fn root_matches_full_tree_and_recent_proofs_verify() {
+            let leaves: Vec<[u8; 32]> = (0..1000u32).map(|i| sha256(&i.to_le_bytes())).collect();
+            let mut windowed = WindowedTree::new(16);
+            for (count, leaf) in leaves.iter().enumerate() {
+                windowed.push_leaf_hash(*leaf);
+                if count % 97 == 0 {
+                    let full = MerkleTree::from_hashes(&leaves[..=count]);
+                    assert_eq!(windowed.get_root().as_ref(), full.get_root());
+                }
+            }
+            let full = MerkleTree::from_hashes(&leaves);
+            let root = windowed.get_root().unwrap();
+            assert_eq!(Some(&root), full.get_root());
+            assert_eq!(windowed.len(), 1000);
+            for (index, leaf) in leaves.iter().enumerate().skip(984) {
+                let proof = windowed.generate_proof_by_index(index).unwrap();
+                assert_eq!(Some(&proof), full.generate_proof_by_index(index).as_ref());
+                assert!(verify_proof(*leaf, &proof, root));
+            }
+            assert!(windowed.generate_proof_by_index(983).is_none());
+            assert!(windowed.generate_proof_by_index(1000).is_none());
+        }
+        #[test]
+        fn unbounded_window_keeps_every_leaf() {
+            let mut windowed = WindowedTree::new(usize::MAX);
+            let values: Vec<[u8; 1]> = (0..5u8).map(|i| [i]).collect();
+            for value in &values {
+                windowed.push(value);
+            }
+            let full = MerkleTree::from_bytes(&values);
+            assert_eq!(windowed.get_root().as_ref(), full.get_root());
+            assert_eq!(
+                windowed.generate_proof_by_index(0),
+                full.generate_proof_by_index(0)
+            );
+        }
+        #[test]
+        fn matches_full_tree_for_every_small_size() {
+            for window in [0, 1, 3, 4] {
+                let mut windowed = WindowedTree::new(window);
+                assert_eq!(windowed.get_root(), None);
+                let mut values = Vec::new();
+                for i in 0..40u8 {
+                    values.push([i]);
+                    windowed.push(&[i]);
+                    let full = MerkleTree::from_bytes(&values);
+                    assert_eq!(windowed.get_root().as_ref(), full.get_root());
+                    for index in values.len().saturating_sub(window)..values.len() {
+                        assert_eq!(
+                            windowed.generate_proof_by_index(index),
+                            full.generate_proof_by_index(index)
+                        );
+                    }
+                }
+            }
+        }
+    }
+    mod light_snapshots {
+        use super::*;
+        #[test]
+        fn appending_ten_leaves_tracks_full_tree_root() {
+            for padding in [PaddingStrategy::Duplicate, PaddingStrategy::Promote] {
+                let mut tree = MerkleTree::from_bytes_with_padding(&["a", "b", "c"], padding);
+                let mut snapshot = tree.light_snapshot().unwrap();
+                assert_eq!(snapshot.root().as_ref(), tree.get_root());
+                assert_eq!(snapshot.padding_strategy(), padding);
+                for i in 0..10u8 {
+                    let root = snapshot.append(sha256(&[i]));
+                    tree.push(&[i]);
+                    assert_eq!(Some(&root), tree.get_root());
+                    assert_eq!(snapshot.size(), tree.leaves.len());
+                }
+                assert_eq!(snapshot.frontier(), tree.frontier().as_slice());
+            }
+            let values = ["a", "b", "c", "d"];
+            let promote = MerkleTree::from_bytes_with_padding(&values, PaddingStrategy::Promote);
+            let duplicate = MerkleTree::from_bytes(&values);
+            assert_ne!(
+                promote.light_snapshot().unwrap().append(sha256(b"e")),
+                duplicate.light_snapshot().unwrap().append(sha256(b"e"))
+            );
+        }
+        #[test]
+        fn snapshot_is_rebuilt_from_size_and_frontier() {
+            let values = ["a", "b", "c", "d", "e", "f"];
+            for padding in [PaddingStrategy::Duplicate, PaddingStrategy::Promote] {
+                let tree = MerkleTree::from_bytes_with_padding(&values, padding);
+                let snapshot = LightSnapshot::new(6, tree.frontier())
+                    .unwrap()
+                    .with_padding_strategy(padding);
+                assert_eq!(snapshot, tree.light_snapshot().unwrap());
+            }
+            let frontier = MerkleTree::from_bytes(&values).frontier();
+            assert_eq!(LightSnapshot::new(4, frontier.clone()), None);
+            assert_eq!(LightSnapshot::new(7, frontier), None);
+            let mut empty = LightSnapshot::new(0, Vec::new()).unwrap();
+            assert_eq!(empty.root(), None);
+            assert_eq!(empty.append(sha256(b"a")), sha256(b"a"));
+        }
+        #[test]
+        fn fixed_depth_trees_have_no_snapshot() {
+            let tree = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c"], 4).unwrap();
+            assert_eq!(tree.light_snapshot(), None);
+        }
+        #[test]
+        fn snapshot_of_empty_tree_starts_without_root() {
+            let tree = MerkleTree::from_bytes::<&str>(&[]);
+            let mut snapshot = tree.light_snapshot().unwrap();
+            assert_eq!(snapshot.root(), None);
+            assert_eq!(
+                snapshot.append(sha256("a".as_bytes())),
+                sha256("a".as_bytes())
+            );
+        }
+    }
+}
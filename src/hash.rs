@@ -1,7 +1,123 @@
+use alloc::vec::Vec;
 use sha2::{Digest, Sha256};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
 pub fn sha256(data: &[u8]) -> [u8; 32] {
     let mut hash = Sha256::new();
     hash.update(data);
     hash.finalize().into()
 }
+
+pub fn leaf_hash_u64(value: u64, endian: Endian) -> [u8; 32] {
+    let bytes = match endian {
+        Endian::Big => value.to_be_bytes(),
+        Endian::Little => value.to_le_bytes(),
+    };
+    return sha256(&bytes);
+}
+
+pub fn commitment(value: &[u8], nonce: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(nonce.len() + value.len());
+    data.extend_from_slice(nonce);
+    data.extend_from_slice(value);
+    return sha256(&data);
+}
+
+pub fn kv_leaf_hash(key: &[u8], value: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(8 + key.len() + value.len());
+    data.extend_from_slice(&(key.len() as u64).to_le_bytes());
+    data.extend_from_slice(key);
+    data.extend_from_slice(value);
+    return sha256(&data);
+}
+
+pub fn log_entry_hash(seq: u64, entry: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(8 + entry.len());
+    data.extend_from_slice(&seq.to_le_bytes());
+    data.extend_from_slice(entry);
+    return sha256(&data);
+}
+
+pub fn version_tagged_root(version: u32, root: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(4 + root.len());
+    data.extend_from_slice(&version.to_le_bytes());
+    data.extend_from_slice(root);
+    return sha256(&data);
+}
+
+pub trait Hasher<const N: usize = 32> {
+    fn digest(&self, data: &[u8]) -> [u8; N];
+    fn hash_leaf(&self, data: &[u8]) -> [u8; N] {
+        return self.digest(data);
+    }
+    fn hash_nodes(&self, left: &[u8; N], right: &[u8; N]) -> [u8; N] {
+        let mut data = Vec::with_capacity(left.len() + right.len());
+        data.extend_from_slice(left);
+        data.extend_from_slice(right);
+        return self.digest(&data);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    fn digest(&self, data: &[u8]) -> [u8; 32] {
+        return sha256(data);
+    }
+}
+
+#[cfg(feature = "blake3")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Blake3Hasher {
+    key: Option<[u8; 32]>,
+}
+
+#[cfg(feature = "blake3")]
+impl Blake3Hasher {
+    pub fn new() -> Self {
+        return Blake3Hasher::default();
+    }
+    pub fn keyed(key: [u8; 32]) -> Self {
+        return Blake3Hasher { key: Some(key) };
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl Hasher for Blake3Hasher {
+    fn digest(&self, data: &[u8]) -> [u8; 32] {
+        return match &self.key {
+            Some(key) => blake3::keyed_hash(key, data).into(),
+            None => blake3::hash(data).into(),
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HybridHasher<L, R> {
+    leaf: L,
+    node: R,
+}
+
+impl<L, R> HybridHasher<L, R> {
+    pub fn new(leaf: L, node: R) -> Self {
+        return HybridHasher { leaf, node };
+    }
+}
+
+impl<L: Hasher<N>, R: Hasher<N>, const N: usize> Hasher<N> for HybridHasher<L, R> {
+    fn digest(&self, data: &[u8]) -> [u8; N] {
+        return self.leaf.digest(data);
+    }
+    fn hash_leaf(&self, data: &[u8]) -> [u8; N] {
+        return self.leaf.hash_leaf(data);
+    }
+    fn hash_nodes(&self, left: &[u8; N], right: &[u8; N]) -> [u8; N] {
+        return self.node.hash_nodes(left, right);
+    }
+}
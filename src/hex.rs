@@ -0,0 +1,31 @@
+use crate::error::HexError;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        hex.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    return hex;
+}
+
+pub(crate) fn decode32(hex: &str) -> Result<[u8; 32], HexError> {
+    let digits = hex.as_bytes();
+    if digits.len() != 64 {
+        return Err(HexError::InvalidLength { len: digits.len() });
+    }
+    let mut bytes = [0u8; 32];
+    for (i, pair) in digits.chunks_exact(2).enumerate() {
+        bytes[i] = hex_digit(pair[0], 2 * i)? << 4 | hex_digit(pair[1], 2 * i + 1)?;
+    }
+    return Ok(bytes);
+}
+
+fn hex_digit(digit: u8, index: usize) -> Result<u8, HexError> {
+    return match (digit as char).to_digit(16) {
+        Some(value) => Ok(value as u8),
+        None => Err(HexError::InvalidDigit { index }),
+    };
+}
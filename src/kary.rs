@@ -0,0 +1,206 @@
+use crate::error::BuildError;
+use crate::hash::sha256;
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KaryProofStep {
+    pub position: usize,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KaryTree {
+    arity: usize,
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl KaryTree {
+    pub fn from_bytes_arity<T: AsRef<[u8]>>(
+        values: &[T],
+        arity: usize,
+    ) -> Result<Self, BuildError> {
+        if arity < 2 {
+            return Err(BuildError::InvalidArity { arity });
+        }
+        let mut levels = Vec::new();
+        let mut current: Vec<[u8; 32]> =
+            values.iter().map(|value| sha256(value.as_ref())).collect();
+        while current.len() > 1 {
+            let parents = current
+                .chunks(arity)
+                .map(|children| hash_children(children, arity))
+                .collect();
+            levels.push(current);
+            current = parents;
+        }
+        if !current.is_empty() {
+            levels.push(current);
+        }
+        return Ok(KaryTree { arity, levels });
+    }
+    pub fn arity(&self) -> usize {
+        return self.arity;
+    }
+    pub fn len(&self) -> usize {
+        return self.levels.first().map_or(0, Vec::len);
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.levels.is_empty();
+    }
+    pub fn depth(&self) -> usize {
+        return self.levels.len();
+    }
+    pub fn get_root(&self) -> Option<&[u8; 32]> {
+        return self.levels.last().map(|level| &level[0]);
+    }
+    pub fn generate_proof_by_index(&self, index: usize) -> Option<Vec<KaryProofStep>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut proof = Vec::with_capacity(self.depth() - 1);
+        let mut node = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let start = node - node % self.arity;
+            let end = (start + self.arity).min(level.len());
+            let siblings = (start..end)
+                .filter(|i| *i != node)
+                .map(|i| level[i])
+                .collect();
+            proof.push(KaryProofStep {
+                position: node - start,
+                siblings,
+            });
+            node /= self.arity;
+        }
+        return Some(proof);
+    }
+}
+
+pub fn verify_proof_arity(
+    leaf_hash: [u8; 32],
+    proof: &[KaryProofStep],
+    root_hash: [u8; 32],
+    arity: usize,
+) -> bool {
+    if arity < 2 {
+        return false;
+    }
+    let mut current = leaf_hash;
+    let mut children = Vec::with_capacity(arity);
+    for step in proof {
+        if step.siblings.len() >= arity || step.position > step.siblings.len() {
+            return false;
+        }
+        children.clear();
+        children.extend_from_slice(&step.siblings[..step.position]);
+        children.push(current);
+        children.extend_from_slice(&step.siblings[step.position..]);
+        current = hash_children(&children, arity);
+    }
+    return current == root_hash;
+}
+
+fn hash_children(children: &[[u8; 32]], arity: usize) -> [u8; 32] {
+    let last = children[children.len() - 1];
+    let mut data = Vec::with_capacity(32 * arity);
+    for i in 0..arity {
+        data.extend_from_slice(children.get(i).unwrap_or(&last));
+    }
+    return sha256(&data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MerkleTree;
+    fn values(count: u64) -> Vec<[u8; 8]> {
+        return (0..count).map(u64::to_le_bytes).collect();
+    }
+    mod building {
+        use super::*;
+        #[test]
+        fn binary_arity_matches_merkle_tree() {
+            for count in 1..=9 {
+                let values = values(count);
+                let tree = KaryTree::from_bytes_arity(&values, 2).unwrap();
+                let binary = MerkleTree::from_bytes(&values);
+                assert_eq!(tree.get_root(), binary.get_root());
+                assert_eq!(tree.depth(), binary.depth());
+            }
+        }
+        #[test]
+        fn ternary_tree_is_shallower_than_binary() {
+            let values = values(27);
+            let tree = KaryTree::from_bytes_arity(&values, 3).unwrap();
+            assert_eq!(tree.arity(), 3);
+            assert_eq!(tree.depth(), 4);
+            assert_eq!(MerkleTree::from_bytes(&values).depth(), 6);
+            assert_eq!(tree.generate_proof_by_index(0).unwrap().len(), 3);
+        }
+        #[test]
+        fn invalid_arity_and_empty_input() {
+            assert_eq!(
+                KaryTree::from_bytes_arity(&values(3), 1),
+                Err(BuildError::InvalidArity { arity: 1 })
+            );
+            let empty = KaryTree::from_bytes_arity::<&[u8]>(&[], 3).unwrap();
+            assert!(empty.is_empty());
+            assert_eq!(empty.get_root(), None);
+            assert_eq!(empty.generate_proof_by_index(0), None);
+        }
+    }
+    mod proofs {
+        use super::*;
+        #[test]
+        fn every_ternary_proof_verifies() {
+            for count in 1..=12 {
+                let values = values(count);
+                let tree = KaryTree::from_bytes_arity(&values, 3).unwrap();
+                let root = *tree.get_root().unwrap();
+                for (index, value) in values.iter().enumerate() {
+                    let proof = tree.generate_proof_by_index(index).unwrap();
+                    assert!(verify_proof_arity(sha256(value), &proof, root, 3));
+                    assert!(!verify_proof_arity(sha256(b"x"), &proof, root, 3));
+                    assert_eq!(
+                        verify_proof_arity(sha256(value), &proof, root, 4),
+                        count == 1
+                    );
+                }
+                assert_eq!(tree.generate_proof_by_index(values.len()), None);
+            }
+        }
+        #[test]
+        fn steps_carry_up_to_arity_minus_one_siblings() {
+            let values = values(10);
+            let tree = KaryTree::from_bytes_arity(&values, 3).unwrap();
+            let proof = tree.generate_proof_by_index(9).unwrap();
+            assert!(proof[0].siblings.is_empty());
+            assert_eq!(proof[0].position, 0);
+            assert!(proof[1].siblings.is_empty());
+            assert_eq!(proof[2].siblings.len(), 1);
+            assert_eq!(proof[2].position, 1);
+            let proof = tree.generate_proof_by_index(4).unwrap();
+            assert_eq!(
+                proof[0].siblings,
+                vec![sha256(&values[3]), sha256(&values[5])]
+            );
+            assert_eq!(proof[0].position, 1);
+        }
+        #[test]
+        fn malformed_steps_are_rejected() {
+            let values = values(9);
+            let tree = KaryTree::from_bytes_arity(&values, 3).unwrap();
+            let root = *tree.get_root().unwrap();
+            let leaf = sha256(&values[4]);
+            let mut proof = tree.generate_proof_by_index(4).unwrap();
+            proof[0].position = 3;
+            assert!(!verify_proof_arity(leaf, &proof, root, 3));
+            proof[0].position = 0;
+            assert!(!verify_proof_arity(leaf, &proof, root, 3));
+            proof[0].position = 1;
+            proof[0].siblings.push(leaf);
+            assert!(!verify_proof_arity(leaf, &proof, root, 3));
+            assert!(!verify_proof_arity(leaf, &[], root, 1));
+        }
+    }
+}
@@ -1,4 +1,26 @@
 #![doc = include_str!("../docs/README.md")]
+#![allow(clippy::needless_return)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
+pub mod builder;
+pub mod consistency;
+pub mod ct;
+pub mod error;
+pub mod frontier;
 pub mod hash;
+#[cfg(feature = "std")]
+mod hex;
+pub mod kary;
+pub mod matrix;
+pub mod mmr;
+pub mod multiproof;
+pub mod position;
+pub mod proof;
+pub mod proto;
+#[cfg(feature = "rlp")]
+pub mod rlp;
+pub mod smt;
 pub mod tree;
+#[cfg(feature = "testing")]
+pub mod vectors;
 pub use tree::MerkleTree;
@@ -0,0 +1,78 @@
+use crate::tree::{Direction, MerkleTree};
+use alloc::vec::Vec;
+
+pub struct MatrixTree {
+    rows: Vec<MerkleTree>,
+    top: MerkleTree,
+}
+
+impl MatrixTree {
+    pub fn new(rows: &[Vec<Vec<u8>>]) -> Self {
+        return MatrixTree {
+            rows: rows.iter().map(|row| MerkleTree::from_bytes(row)).collect(),
+            top: MerkleTree::from_matrix(rows),
+        };
+    }
+    pub fn get_root(&self) -> Option<&[u8; 32]> {
+        return self.top.get_root();
+    }
+    pub fn row_root(&self, row: usize) -> Option<[u8; 32]> {
+        return self.top.leaves.get(row).copied();
+    }
+    pub fn row_proof(&self, row: usize, col: usize) -> Option<Vec<([u8; 32], Direction)>> {
+        let mut proof = self.rows.get(row)?.generate_proof_by_index(col)?;
+        proof.extend(self.top.generate_proof_by_index(row)?);
+        return Some(proof);
+    }
+}
+
+impl MerkleTree {
+    pub fn from_matrix(rows: &[Vec<Vec<u8>>]) -> Self {
+        return MerkleTree::from_chunked_values(rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256;
+    use crate::tree::verify_proof;
+    fn matrix(height: u8, width: u8) -> Vec<Vec<Vec<u8>>> {
+        return (0..height)
+            .map(|row| (0..width).map(|col| vec![row, col]).collect())
+            .collect();
+    }
+    mod matrix_commitment {
+        use super::*;
+        #[test]
+        fn root_is_tree_over_row_roots() {
+            let rows = matrix(3, 4);
+            let row_roots: Vec<[u8; 32]> = rows
+                .iter()
+                .map(|row| *MerkleTree::from_bytes(row).get_root().unwrap())
+                .collect();
+            let expected = MerkleTree::from_leaf_hashes_vec(row_roots.clone());
+            let tree = MerkleTree::from_matrix(&rows);
+            assert_eq!(tree.get_root(), expected.get_root());
+            let matrix_tree = MatrixTree::new(&rows);
+            assert_eq!(matrix_tree.get_root(), expected.get_root());
+            assert_eq!(matrix_tree.row_root(2), Some(row_roots[2]));
+        }
+        #[test]
+        fn element_1_2_of_3x4_matrix_is_proven() {
+            let rows = matrix(3, 4);
+            let matrix_tree = MatrixTree::new(&rows);
+            let root = *matrix_tree.get_root().unwrap();
+            let proof = matrix_tree.row_proof(1, 2).unwrap();
+            assert_eq!(proof.len(), 2 + 2);
+            assert!(verify_proof(sha256(&rows[1][2]), &proof, root));
+            assert!(!verify_proof(sha256(&rows[1][3]), &proof, root));
+        }
+        #[test]
+        fn out_of_range_cells_have_no_proof() {
+            let matrix_tree = MatrixTree::new(&matrix(3, 4));
+            assert!(matrix_tree.row_proof(3, 0).is_none());
+            assert!(matrix_tree.row_proof(0, 4).is_none());
+        }
+    }
+}
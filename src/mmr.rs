@@ -0,0 +1,141 @@
+use crate::hash::sha256;
+use crate::tree::{Direction, hash_pair};
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerkleMountainRange {
+    size: usize,
+    peaks: Vec<[u8; 32]>,
+    last_path: Vec<[u8; 32]>,
+}
+
+impl MerkleMountainRange {
+    pub fn new() -> Self {
+        return MerkleMountainRange::default();
+    }
+    pub fn len(&self) -> usize {
+        return self.size;
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.size == 0;
+    }
+    pub fn peaks(&self) -> &[[u8; 32]] {
+        return &self.peaks;
+    }
+    pub fn append(&mut self, value: &[u8]) -> usize {
+        return self.append_leaf_hash(sha256(value));
+    }
+    pub fn append_leaf_hash(&mut self, leaf_hash: [u8; 32]) -> usize {
+        self.last_path.clear();
+        let mut node = leaf_hash;
+        let mut remaining = self.size;
+        while remaining & 1 == 1 {
+            let left = self.peaks.pop().expect("peaks match the size");
+            self.last_path.push(left);
+            node = hash_pair(&left, &node);
+            remaining >>= 1;
+        }
+        self.peaks.push(node);
+        self.size += 1;
+        return self.size - 1;
+    }
+    pub fn root(&self) -> Option<[u8; 32]> {
+        let mut peaks = self.peaks.iter().rev();
+        let mut root = *peaks.next()?;
+        for peak in peaks {
+            root = hash_pair(peak, &root);
+        }
+        return Some(root);
+    }
+    pub fn generate_latest_leaf_proof(&self, index: usize) -> Option<Vec<([u8; 32], Direction)>> {
+        if self.size.checked_sub(1) != Some(index) {
+            return None;
+        }
+        let left_peaks = &self.peaks[..self.peaks.len() - 1];
+        let proof = self
+            .last_path
+            .iter()
+            .chain(left_peaks.iter().rev())
+            .map(|sibling| (*sibling, Direction::Left))
+            .collect();
+        return Some(proof);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::{MerkleTree, PaddingStrategy, verify_proof};
+    fn value(i: u64) -> [u8; 8] {
+        return i.to_le_bytes();
+    }
+    mod appending {
+        use super::*;
+        #[test]
+        fn peaks_follow_the_binary_size() {
+            let mut mmr = MerkleMountainRange::new();
+            assert_eq!(mmr.root(), None);
+            for i in 1..=100u64 {
+                assert_eq!(mmr.append(&value(i)), i as usize - 1);
+                assert_eq!(mmr.len(), i as usize);
+                assert_eq!(mmr.peaks().len(), i.count_ones() as usize);
+            }
+        }
+        #[test]
+        fn root_matches_promoted_tree() {
+            let mut mmr = MerkleMountainRange::new();
+            let mut values = Vec::new();
+            for i in 1..=100u64 {
+                mmr.append(&value(i));
+                values.push(value(i));
+                let tree = MerkleTree::from_bytes_with_padding(&values, PaddingStrategy::Promote);
+                assert_eq!(mmr.root().as_ref(), tree.get_root());
+            }
+        }
+    }
+    mod proofs {
+        use super::*;
+        #[test]
+        fn latest_leaf_proof_verifies_against_root() {
+            let mut mmr = MerkleMountainRange::new();
+            for i in 1..=100u64 {
+                let index = mmr.append(&value(i));
+                if [1, 2, 7, 8, 33, 64, 99, 100].contains(&i) {
+                    let proof = mmr.generate_latest_leaf_proof(index).unwrap();
+                    assert!(verify_proof(sha256(&value(i)), &proof, mmr.root().unwrap()));
+                    assert!(!verify_proof(
+                        sha256(&value(i + 1)),
+                        &proof,
+                        mmr.root().unwrap()
+                    ));
+                }
+            }
+        }
+        #[test]
+        fn proof_matches_promoted_tree_proof() {
+            let values: Vec<[u8; 8]> = (1..=13).map(value).collect();
+            let mut mmr = MerkleMountainRange::new();
+            for value in &values {
+                mmr.append(value);
+            }
+            let tree = MerkleTree::from_bytes_with_padding(&values, PaddingStrategy::Promote);
+            assert_eq!(
+                mmr.generate_latest_leaf_proof(12),
+                tree.generate_proof_by_index(12)
+            );
+        }
+        #[test]
+        fn only_latest_leaf_is_provable() {
+            let mut mmr = MerkleMountainRange::new();
+            assert_eq!(mmr.generate_latest_leaf_proof(0), None);
+            assert_eq!(mmr.generate_latest_leaf_proof(usize::MAX), None);
+            for i in 0..5 {
+                mmr.append(&value(i));
+            }
+            assert_eq!(mmr.generate_latest_leaf_proof(3), None);
+            assert_eq!(mmr.generate_latest_leaf_proof(5), None);
+            assert_eq!(mmr.generate_latest_leaf_proof(usize::MAX), None);
+            assert!(mmr.generate_latest_leaf_proof(4).is_some());
+        }
+    }
+}
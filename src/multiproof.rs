@@ -0,0 +1,502 @@
+use crate::hash::{Hasher, Sha256Hasher};
+use crate::tree::{
+    Direction, MerkleTree, PaddingStrategy, build_levels, fold_step, hash_pair,
+    index_from_directions,
+};
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OzMultiProof {
+    pub leaves: Vec<[u8; 32]>,
+    pub proof: Vec<[u8; 32]>,
+    pub flags: Vec<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiProof {
+    pub leaf_count: usize,
+    pub indices: Vec<usize>,
+    pub siblings: Vec<[u8; 32]>,
+    pub padding: PaddingStrategy,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedProof {
+    pub branch_a: Vec<([u8; 32], Direction)>,
+    pub branch_b: Vec<([u8; 32], Direction)>,
+    pub shared: Vec<([u8; 32], Direction)>,
+}
+
+impl MerkleTree {
+    pub fn generate_multiproof(&self, indices: &[usize]) -> Option<MultiProof> {
+        let mut known = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        if known.is_empty() || *known.last()? >= self.leaves.len() {
+            return None;
+        }
+        let levels = build_levels(&self.leaves, &Sha256Hasher, self.padding);
+        let proven = known.clone();
+        let mut siblings = Vec::new();
+        for level in &levels[..levels.len() - 1] {
+            let mut parents = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let position = known[i];
+                if position.is_multiple_of(2) && known.get(i + 1) == Some(&(position + 1)) {
+                    i += 2;
+                } else {
+                    if let Some(sibling) = level.get(position ^ 1) {
+                        siblings.push(*sibling);
+                    }
+                    i += 1;
+                }
+                parents.push(position / 2);
+            }
+            known = parents;
+        }
+        return Some(MultiProof {
+            leaf_count: self.leaves.len(),
+            indices: proven,
+            siblings,
+            padding: self.padding,
+        });
+    }
+}
+
+impl<H: Hasher> MerkleTree<H> {
+    pub fn sorted_pair_root(&self) -> Option<[u8; 32]> {
+        return build_oz_tree(&self.leaves, &self.hasher).first().copied();
+    }
+    pub fn generate_oz_multiproof(&self, indices: &[usize]) -> Option<OzMultiProof> {
+        let mut known = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        if known.is_empty() || *known.last()? >= self.leaves.len() {
+            return None;
+        }
+        let tree = build_oz_tree(&self.leaves, &self.hasher);
+        let leaves = known.iter().map(|i| self.leaves[*i]).collect();
+        let mut stack: VecDeque<usize> = known.iter().map(|i| tree.len() - 1 - i).collect();
+        let mut proof = Vec::new();
+        let mut flags = Vec::new();
+        while let Some(node) = stack.pop_front().filter(|node| *node > 0) {
+            let sibling = if node % 2 == 1 { node + 1 } else { node - 1 };
+            if stack.front() == Some(&sibling) {
+                flags.push(true);
+                stack.pop_front();
+            } else {
+                flags.push(false);
+                proof.push(tree[sibling]);
+            }
+            stack.push_back((node - 1) / 2);
+        }
+        return Some(OzMultiProof {
+            leaves,
+            proof,
+            flags,
+        });
+    }
+}
+
+pub fn verify_multiproof(leaves: &[[u8; 32]], proof: &MultiProof, root: [u8; 32]) -> bool {
+    return process_multiproof(leaves, proof) == Some(root);
+}
+
+fn process_multiproof(leaves: &[[u8; 32]], proof: &MultiProof) -> Option<[u8; 32]> {
+    let indices = &proof.indices;
+    if leaves.is_empty()
+        || leaves.len() != indices.len()
+        || indices.windows(2).any(|pair| pair[0] >= pair[1])
+        || *indices.last()? >= proof.leaf_count
+    {
+        return None;
+    }
+    let mut known: Vec<(usize, [u8; 32])> = indices
+        .iter()
+        .copied()
+        .zip(leaves.iter().copied())
+        .collect();
+    let mut siblings = proof.siblings.iter();
+    let mut level_len = proof.leaf_count;
+    while level_len > 1 {
+        let mut parents = Vec::with_capacity(known.len());
+        let mut i = 0;
+        while i < known.len() {
+            let (position, hash) = known[i];
+            let parent = match known.get(i + 1) {
+                Some((next, next_hash)) if position.is_multiple_of(2) && *next == position + 1 => {
+                    i += 1;
+                    hash_pair(&hash, next_hash)
+                }
+                _ if !position.is_multiple_of(2) => hash_pair(siblings.next()?, &hash),
+                _ if position + 1 < level_len => hash_pair(&hash, siblings.next()?),
+                _ => match proof.padding {
+                    PaddingStrategy::Duplicate => hash_pair(&hash, &hash),
+                    PaddingStrategy::Promote => hash,
+                },
+            };
+            parents.push((position / 2, parent));
+            i += 1;
+        }
+        known = parents;
+        level_len = level_len.div_ceil(2);
+    }
+    if siblings.next().is_some() {
+        return None;
+    }
+    return Some(known[0].1);
+}
+
+pub fn verify_oz_multiproof(multiproof: &OzMultiProof, root: [u8; 32]) -> bool {
+    return verify_oz_multiproof_with(&Sha256Hasher, multiproof, root);
+}
+
+pub fn verify_oz_multiproof_with<H: Hasher>(
+    hasher: &H,
+    multiproof: &OzMultiProof,
+    root: [u8; 32],
+) -> bool {
+    return process_oz_multiproof(hasher, multiproof) == Some(root);
+}
+
+fn process_oz_multiproof<H: Hasher>(hasher: &H, multiproof: &OzMultiProof) -> Option<[u8; 32]> {
+    let leaves = &multiproof.leaves;
+    let proof = &multiproof.proof;
+    let flags = &multiproof.flags;
+    if leaves.len() + proof.len() != flags.len() + 1 {
+        return None;
+    }
+    let mut hashes: Vec<[u8; 32]> = Vec::with_capacity(flags.len());
+    let mut leaf_pos = 0;
+    let mut hash_pos = 0;
+    let mut proof_pos = 0;
+    for flag in flags {
+        let a = if leaf_pos < leaves.len() {
+            leaf_pos += 1;
+            leaves[leaf_pos - 1]
+        } else {
+            hash_pos += 1;
+            *hashes.get(hash_pos - 1)?
+        };
+        let b = if !flag {
+            proof_pos += 1;
+            *proof.get(proof_pos - 1)?
+        } else if leaf_pos < leaves.len() {
+            leaf_pos += 1;
+            leaves[leaf_pos - 1]
+        } else {
+            hash_pos += 1;
+            *hashes.get(hash_pos - 1)?
+        };
+        hashes.push(hash_sorted_pair(hasher, &a, &b));
+    }
+    if let Some(last) = hashes.last() {
+        if proof_pos != proof.len() {
+            return None;
+        }
+        return Some(*last);
+    } else if let Some(leaf) = leaves.first() {
+        return Some(*leaf);
+    } else {
+        return proof.first().copied();
+    }
+}
+
+pub fn merge_adjacent_proofs(
+    index_a: usize,
+    proof_a: &[([u8; 32], Direction)],
+    index_b: usize,
+    proof_b: &[([u8; 32], Direction)],
+) -> Option<SharedProof> {
+    if index_a.checked_add(1) != Some(index_b)
+        || proof_a.len() != proof_b.len()
+        || index_from_directions(proof_a) != Some(index_a)
+        || index_from_directions(proof_b) != Some(index_b)
+    {
+        return None;
+    }
+    let split = (usize::BITS - (index_a ^ index_b).leading_zeros()) as usize;
+    if split > proof_a.len() || proof_a[split..] != proof_b[split..] {
+        return None;
+    }
+    return Some(SharedProof {
+        branch_a: proof_a[..split - 1].to_vec(),
+        branch_b: proof_b[..split - 1].to_vec(),
+        shared: proof_a[split..].to_vec(),
+    });
+}
+
+pub fn verify_shared_proof(
+    leaf_a: [u8; 32],
+    leaf_b: [u8; 32],
+    shared: &SharedProof,
+    root: [u8; 32],
+) -> bool {
+    let mut node_a = leaf_a;
+    for (sibling_hash, direction) in &shared.branch_a {
+        node_a = fold_step(&node_a, sibling_hash, direction);
+    }
+    let mut node_b = leaf_b;
+    for (sibling_hash, direction) in &shared.branch_b {
+        node_b = fold_step(&node_b, sibling_hash, direction);
+    }
+    let mut current = hash_pair(&node_a, &node_b);
+    for (sibling_hash, direction) in &shared.shared {
+        current = fold_step(&current, sibling_hash, direction);
+    }
+    return current == root;
+}
+
+fn hash_sorted_pair<H: Hasher>(hasher: &H, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    return hasher.hash_nodes(first, second);
+}
+
+fn build_oz_tree<H: Hasher>(leaves: &[[u8; 32]], hasher: &H) -> Vec<[u8; 32]> {
+    if leaves.is_empty() {
+        return Vec::new();
+    }
+    let mut tree = vec![[0u8; 32]; 2 * leaves.len() - 1];
+    let internal = leaves.len() - 1;
+    for (slot, leaf) in tree[internal..].iter_mut().rev().zip(leaves) {
+        *slot = *leaf;
+    }
+    for node in (0..internal).rev() {
+        tree[node] = hash_sorted_pair(hasher, &tree[2 * node + 1], &tree[2 * node + 2]);
+    }
+    return tree;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256;
+    mod shared_proofs {
+        use super::*;
+        #[test]
+        fn merge_proofs_for_first_two_leaves() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let proof_a = tree.generate_proof_by_index(0).unwrap();
+            let proof_b = tree.generate_proof_by_index(1).unwrap();
+            let shared = merge_adjacent_proofs(0, &proof_a, 1, &proof_b).unwrap();
+            assert!(shared.branch_a.is_empty() && shared.branch_b.is_empty());
+            assert_eq!(shared.shared, proof_a[1..].to_vec());
+            let (leaf_a, leaf_b) = (sha256("a".as_bytes()), sha256("b".as_bytes()));
+            assert!(verify_shared_proof(leaf_a, leaf_b, &shared, root));
+            assert!(!verify_shared_proof(leaf_b, leaf_a, &shared, root));
+        }
+        #[test]
+        fn merge_proofs_across_subtrees() {
+            let values: Vec<[u8; 1]> = (0..7u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            for index in 0..values.len() - 1 {
+                let proof_a = tree.generate_proof_by_index(index).unwrap();
+                let proof_b = tree.generate_proof_by_index(index + 1).unwrap();
+                let shared = merge_adjacent_proofs(index, &proof_a, index + 1, &proof_b).unwrap();
+                let (leaf_a, leaf_b) = (sha256(&values[index]), sha256(&values[index + 1]));
+                assert!(verify_shared_proof(leaf_a, leaf_b, &shared, root));
+            }
+        }
+        #[test]
+        fn non_adjacent_or_mismatched_proofs_are_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let proof_0 = tree.generate_proof_by_index(0).unwrap();
+            let proof_1 = tree.generate_proof_by_index(1).unwrap();
+            let proof_2 = tree.generate_proof_by_index(2).unwrap();
+            assert!(merge_adjacent_proofs(0, &proof_0, 2, &proof_2).is_none());
+            assert!(merge_adjacent_proofs(1, &proof_1, 2, &proof_1).is_none());
+            assert!(merge_adjacent_proofs(1, &proof_0, 2, &proof_2).is_none());
+        }
+    }
+    mod multiproof {
+        use super::*;
+        #[test]
+        fn shares_siblings_between_leaves_on_a_16_leaf_tree() {
+            let values: Vec<[u8; 1]> = (0..16u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            let indices = [0, 1, 5, 9, 10, 15];
+            let multiproof = tree.generate_multiproof(&indices).unwrap();
+            let naive: usize = indices
+                .iter()
+                .map(|index| tree.generate_proof_by_index(*index).unwrap().len())
+                .sum();
+            assert_eq!(naive, 24);
+            assert_eq!(multiproof.siblings.len(), 7);
+            assert_eq!(multiproof.indices, indices.to_vec());
+            let leaves: Vec<[u8; 32]> = indices.iter().map(|i| sha256(&values[*i])).collect();
+            assert!(verify_multiproof(&leaves, &multiproof, root));
+            let mut tampered = leaves.clone();
+            tampered[3] = sha256(b"z");
+            assert!(!verify_multiproof(&tampered, &multiproof, root));
+            assert!(!verify_multiproof(&leaves[1..], &multiproof, root));
+        }
+        #[test]
+        fn verifies_every_pair_for_both_padding_strategies() {
+            let values: Vec<[u8; 1]> = (0..7u8).map(|i| [i]).collect();
+            for padding in [PaddingStrategy::Duplicate, PaddingStrategy::Promote] {
+                let tree = MerkleTree::from_bytes_with_padding(&values, padding);
+                let root = *tree.get_root().unwrap();
+                for a in 0..values.len() {
+                    for b in a..values.len() {
+                        let multiproof = tree.generate_multiproof(&[b, a]).unwrap();
+                        let leaves: Vec<[u8; 32]> = multiproof
+                            .indices
+                            .iter()
+                            .map(|i| sha256(&values[*i]))
+                            .collect();
+                        assert!(verify_multiproof(&leaves, &multiproof, root));
+                    }
+                }
+            }
+        }
+        #[test]
+        fn malformed_multiproofs_are_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let leaves = [sha256(b"a"), sha256(b"c")];
+            let multiproof = tree.generate_multiproof(&[0, 2]).unwrap();
+            let mut extra = multiproof.clone();
+            extra.siblings.push([0; 32]);
+            assert!(!verify_multiproof(&leaves, &extra, root));
+            let mut unsorted = multiproof.clone();
+            unsorted.indices = vec![2, 0];
+            assert!(!verify_multiproof(&[leaves[1], leaves[0]], &unsorted, root));
+            assert!(tree.generate_multiproof(&[]).is_none());
+            assert!(tree.generate_multiproof(&[4]).is_none());
+        }
+    }
+    mod oz_multiproof {
+        use super::*;
+        struct Keccak256Hasher;
+        impl Hasher for Keccak256Hasher {
+            fn digest(&self, data: &[u8]) -> [u8; 32] {
+                return <sha3::Keccak256 as sha3::Digest>::digest(data).into();
+            }
+        }
+        fn sorted(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+            return hash_sorted_pair(&Sha256Hasher, a, b);
+        }
+        fn hex32(hex: &str) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            for (i, byte) in out.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+            }
+            return out;
+        }
+        #[test]
+        fn standard_merkle_tree_readme_vector() {
+            // StandardMerkleTree example from the @openzeppelin/merkle-tree README.
+            let leaf = |address: u8, amount: u64| {
+                let mut encoded = [0u8; 64];
+                encoded[12..32].fill(address);
+                encoded[56..].copy_from_slice(&amount.to_be_bytes());
+                return Keccak256Hasher.digest(&Keccak256Hasher.digest(&encoded));
+            };
+            let first = leaf(0x11, 5_000_000_000_000_000_000);
+            let second = leaf(0x22, 2_500_000_000_000_000_000);
+            let mut leaves = vec![first, second];
+            leaves.sort_unstable();
+            let tree = MerkleTree::from_leaf_hashes_with(leaves, Keccak256Hasher);
+            let root = hex32("d4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77");
+            assert_eq!(tree.sorted_pair_root(), Some(root));
+            let proof = tree.generate_oz_multiproof(&[1]).unwrap();
+            assert_eq!(proof.leaves, vec![first]);
+            assert_eq!(
+                proof.proof,
+                vec![hex32(
+                    "b92c48e9d7abe27fd8dfd6b5dfdbfb1c9a463f80c712b66f3a5180a090cccafc"
+                )]
+            );
+            assert_eq!(proof.flags, vec![false]);
+            assert!(verify_oz_multiproof_with(&Keccak256Hasher, &proof, root));
+            let both = tree.generate_oz_multiproof(&[0, 1]).unwrap();
+            assert!(both.proof.is_empty());
+            assert_eq!(both.flags, vec![true]);
+            assert!(verify_oz_multiproof_with(&Keccak256Hasher, &both, root));
+            assert!(!verify_oz_multiproof(&both, root));
+        }
+        #[test]
+        fn five_leaf_multiproof_follows_the_complete_tree_layout() {
+            let leaves: Vec<[u8; 32]> = ["a", "b", "c", "d", "e"]
+                .iter()
+                .map(|v| sha256(v.as_bytes()))
+                .collect();
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let left = sorted(&sorted(&leaves[0], &leaves[1]), &leaves[4]);
+            let right = sorted(&leaves[2], &leaves[3]);
+            let root = sorted(&left, &right);
+            assert_eq!(tree.sorted_pair_root(), Some(root));
+            let multiproof = tree.generate_oz_multiproof(&[4, 1]).unwrap();
+            assert_eq!(multiproof.leaves, vec![leaves[1], leaves[4]]);
+            assert_eq!(multiproof.proof, vec![leaves[0], right]);
+            assert_eq!(multiproof.flags, vec![false, true, false]);
+            assert!(verify_oz_multiproof(&multiproof, root));
+            let multiproof = tree.generate_oz_multiproof(&[0, 1, 2, 3]).unwrap();
+            assert_eq!(multiproof.proof, vec![leaves[4]]);
+            assert_eq!(multiproof.flags, vec![true, true, false, true]);
+            assert!(verify_oz_multiproof(&multiproof, root));
+        }
+        #[test]
+        fn three_leaf_tree_has_no_duplicated_node() {
+            let leaves: Vec<[u8; 32]> = ["a", "b", "c"]
+                .iter()
+                .map(|v| sha256(v.as_bytes()))
+                .collect();
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let pair = sorted(&leaves[0], &leaves[1]);
+            let root = sorted(&pair, &leaves[2]);
+            assert_eq!(tree.sorted_pair_root(), Some(root));
+            let multiproof = tree.generate_oz_multiproof(&[2]).unwrap();
+            assert_eq!(multiproof.proof, vec![pair]);
+            assert_eq!(multiproof.flags, vec![false]);
+            let multiproof = tree.generate_oz_multiproof(&[0]).unwrap();
+            assert_eq!(multiproof.proof, vec![leaves[1], leaves[2]]);
+            assert_eq!(multiproof.flags, vec![false, false]);
+            assert!(verify_oz_multiproof(&multiproof, root));
+        }
+        #[test]
+        fn multiproofs_verify_for_every_pair_of_leaves() {
+            let values: Vec<[u8; 1]> = (0..7u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let root = tree.sorted_pair_root().unwrap();
+            for a in 0..values.len() {
+                for b in a..values.len() {
+                    let multiproof = tree.generate_oz_multiproof(&[a, b]).unwrap();
+                    assert!(verify_oz_multiproof(&multiproof, root));
+                }
+            }
+        }
+        #[test]
+        fn tampered_leaf_fails_verification() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = tree.sorted_pair_root().unwrap();
+            let mut multiproof = tree.generate_oz_multiproof(&[0, 2]).unwrap();
+            multiproof.leaves[1] = sha256("z".as_bytes());
+            assert!(!verify_oz_multiproof(&multiproof, root));
+        }
+        #[test]
+        fn invalid_requests_return_none() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert!(tree.generate_oz_multiproof(&[]).is_none());
+            assert!(tree.generate_oz_multiproof(&[0, 3]).is_none());
+            let empty = MerkleTree::from_bytes::<&[u8]>(&[]);
+            assert!(empty.generate_oz_multiproof(&[0]).is_none());
+        }
+        #[test]
+        fn single_leaf_multiproof_has_no_flags() {
+            let tree = MerkleTree::from_bytes(&["only"]);
+            let multiproof = tree.generate_oz_multiproof(&[0]).unwrap();
+            assert!(multiproof.flags.is_empty());
+            assert!(verify_oz_multiproof(
+                &multiproof,
+                tree.sorted_pair_root().unwrap()
+            ));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::hash::sha256;
+use crate::tree::Direction;
+use alloc::vec::Vec;
+
+pub struct PositionBoundTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl PositionBoundTree {
+    pub fn from_bytes<T: AsRef<[u8]>>(values: &[T]) -> Self {
+        let mut levels = Vec::new();
+        if values.is_empty() {
+            return PositionBoundTree { levels };
+        }
+        let mut current: Vec<[u8; 32]> =
+            values.iter().map(|value| sha256(value.as_ref())).collect();
+        while current.len() > 1 {
+            let next = current
+                .chunks(2)
+                .enumerate()
+                .map(|(node_index, pair)| {
+                    hash_bound_pair(&pair[0], pair.get(1).unwrap_or(&pair[0]), node_index as u64)
+                })
+                .collect();
+            levels.push(current);
+            current = next;
+        }
+        levels.push(current);
+        return PositionBoundTree { levels };
+    }
+    pub fn get_root(&self) -> Option<&[u8; 32]> {
+        return self.levels.last().map(|level| &level[0]);
+    }
+    pub fn generate_proof(&self, index: usize) -> Option<Vec<([u8; 32], Direction, u64)>> {
+        if index >= self.levels.first()?.len() {
+            return None;
+        }
+        let mut proof = Vec::with_capacity(self.levels.len() - 1);
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let node_index = (position / 2) as u64;
+            if position.is_multiple_of(2) {
+                let sibling = level.get(position + 1).unwrap_or(&level[position]);
+                proof.push((*sibling, Direction::Right, node_index));
+            } else {
+                proof.push((level[position - 1], Direction::Left, node_index));
+            }
+            position /= 2;
+        }
+        return Some(proof);
+    }
+}
+
+pub fn verify_proof_position_bound(
+    leaf_hash: [u8; 32],
+    proof_with_indices: &[([u8; 32], Direction, u64)],
+    root: [u8; 32],
+) -> bool {
+    let mut current = leaf_hash;
+    for (sibling_hash, direction, node_index) in proof_with_indices {
+        current = match direction {
+            Direction::Left => hash_bound_pair(sibling_hash, &current, *node_index),
+            Direction::Right => hash_bound_pair(&current, sibling_hash, *node_index),
+        };
+    }
+    return current == root;
+}
+
+fn hash_bound_pair(left: &[u8; 32], right: &[u8; 32], node_index: u64) -> [u8; 32] {
+    let mut data = Vec::with_capacity(left.len() + right.len() + 8);
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    data.extend_from_slice(&node_index.to_le_bytes());
+    return sha256(&data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MerkleTree;
+    mod position_binding {
+        use super::*;
+        #[test]
+        fn swapping_subtrees_changes_root() {
+            let tree = PositionBoundTree::from_bytes(&["a", "b", "c", "d"]);
+            let swapped = PositionBoundTree::from_bytes(&["c", "d", "a", "b"]);
+            assert_ne!(tree.get_root(), swapped.get_root());
+        }
+        #[test]
+        fn identical_subtrees_are_bound_to_their_position() {
+            let plain = MerkleTree::from_bytes(&["a", "b", "a", "b"]);
+            let plain_level = plain.level_nodes(1).unwrap();
+            assert_eq!(plain_level[0], plain_level[1]);
+            let bound = PositionBoundTree::from_bytes(&["a", "b", "a", "b"]);
+            assert_ne!(bound.levels[1][0], bound.levels[1][1]);
+            assert_ne!(bound.get_root(), plain.get_root());
+        }
+        #[test]
+        fn proofs_verify_and_indices_are_bound() {
+            let values = ["a", "b", "c", "d", "e"];
+            let tree = PositionBoundTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            for (index, value) in values.iter().enumerate() {
+                let proof = tree.generate_proof(index).unwrap();
+                assert!(verify_proof_position_bound(
+                    sha256(value.as_bytes()),
+                    &proof,
+                    root
+                ));
+            }
+            let mut proof = tree.generate_proof(2).unwrap();
+            proof[0].2 += 1;
+            assert!(!verify_proof_position_bound(
+                sha256("c".as_bytes()),
+                &proof,
+                root
+            ));
+            assert!(tree.generate_proof(5).is_none());
+        }
+        #[test]
+        fn empty_and_single_leaf_trees() {
+            let empty = PositionBoundTree::from_bytes::<&str>(&[]);
+            assert!(empty.get_root().is_none());
+            assert!(empty.generate_proof(0).is_none());
+            let single = PositionBoundTree::from_bytes(&["a"]);
+            assert_eq!(single.get_root(), Some(&sha256("a".as_bytes())));
+            assert_eq!(single.generate_proof(0), Some(vec![]));
+        }
+    }
+}
@@ -0,0 +1,553 @@
+use crate::error::DecodeError;
+#[cfg(feature = "std")]
+use crate::tree::fold_step;
+use crate::tree::{Direction, MerkleTree, verify_proof};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfContainedProof {
+    pub root: [u8; 32],
+    pub leaf: [u8; 32],
+    pub steps: Vec<([u8; 32], Direction)>,
+}
+
+impl SelfContainedProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(68 + self.steps.len() * 33);
+        bytes.extend_from_slice(&self.root);
+        bytes.extend_from_slice(&self.leaf);
+        encode_steps(&self.steps, &mut bytes);
+        return bytes;
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (proof, used) = decode_self_contained(bytes)?;
+        if bytes.len() > used {
+            return Err(DecodeError::TrailingBytes {
+                count: bytes.len() - used,
+            });
+        }
+        return Ok(proof);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedRoot {
+    pub root: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Proof(pub Vec<([u8; 32], Direction)>);
+
+impl Proof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.0.len() * 33);
+        encode_steps(&self.0, &mut bytes);
+        return bytes;
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (steps, used) = decode_steps(bytes)?;
+        if bytes.len() > used {
+            return Err(DecodeError::TrailingBytes {
+                count: bytes.len() - used,
+            });
+        }
+        return Ok(Proof(steps));
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Proof {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for (sibling, direction) in &self.0 {
+            seq.serialize_element(&(SiblingHash(*sibling), direction))?;
+        }
+        return seq.end();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Proof {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let steps: Vec<(SiblingHash, Direction)> = serde::Deserialize::deserialize(deserializer)?;
+        return Ok(Proof(
+            steps
+                .into_iter()
+                .map(|(sibling, direction)| (sibling.0, direction))
+                .collect(),
+        ));
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SiblingHash([u8; 32]);
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SiblingHash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.serialize_str(&crate::hex::encode(&self.0));
+        }
+        return serializer.serialize_bytes(&self.0);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SiblingHash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            return deserializer.deserialize_str(SiblingHashVisitor);
+        }
+        return deserializer.deserialize_bytes(SiblingHashVisitor);
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SiblingHashVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for SiblingHashVisitor {
+    type Value = SiblingHash;
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return formatter.write_str("a 32-byte hash as hex or raw bytes");
+    }
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<SiblingHash, E> {
+        let hash = crate::hex::decode32(value)
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(value), &self))?;
+        return Ok(SiblingHash(hash));
+    }
+    fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<SiblingHash, E> {
+        let hash = value
+            .try_into()
+            .map_err(|_| E::invalid_length(value.len(), &self))?;
+        return Ok(SiblingHash(hash));
+    }
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<SiblingHash, A::Error> {
+        let mut hash = [0u8; 32];
+        for (i, byte) in hash.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(serde::de::Error::invalid_length(33, &self));
+        }
+        return Ok(SiblingHash(hash));
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AggregateProof {
+    pub entries: Vec<SelfContainedProof>,
+}
+
+impl AggregateProof {
+    pub fn all_valid(&self) -> bool {
+        return self.entries.iter().all(verify_self_contained);
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.entries.len() as u32).to_be_bytes().to_vec();
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.to_bytes());
+        }
+        return bytes;
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let header = bytes.get(..4).ok_or(DecodeError::Truncated)?;
+        let count = u32::from_be_bytes(header.try_into().unwrap()) as usize;
+        let mut offset = 4;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let (entry, used) = decode_self_contained(&bytes[offset..])?;
+            entries.push(entry);
+            offset += used;
+        }
+        if bytes.len() > offset {
+            return Err(DecodeError::TrailingBytes {
+                count: bytes.len() - offset,
+            });
+        }
+        return Ok(AggregateProof { entries });
+    }
+}
+
+impl MerkleTree {
+    pub fn self_contained_proof(&self, index: usize) -> Option<SelfContainedProof> {
+        let steps = self.generate_proof_by_index(index)?;
+        return Some(SelfContainedProof {
+            root: *self.get_root()?,
+            leaf: self.leaves[index],
+            steps,
+        });
+    }
+}
+
+pub fn verify_self_contained(p: &SelfContainedProof) -> bool {
+    return verify_proof(p.leaf, &p.steps, p.root);
+}
+
+pub fn verify_proof_signed<F>(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    signed_root: &SignedRoot,
+    pubkey: &[u8],
+    verify_fn: F,
+) -> bool
+where
+    F: FnOnce(&[u8; 32], &[u8], &[u8]) -> bool,
+{
+    if !verify_fn(&signed_root.root, &signed_root.signature, pubkey) {
+        return false;
+    }
+    return verify_proof(leaf_hash, proof, signed_root.root);
+}
+
+pub fn verify_aggregate(bundle: &AggregateProof) -> Vec<bool> {
+    return bundle.entries.iter().map(verify_self_contained).collect();
+}
+
+fn decode_self_contained(bytes: &[u8]) -> Result<(SelfContainedProof, usize), DecodeError> {
+    let header = bytes.get(..64).ok_or(DecodeError::Truncated)?;
+    let root: [u8; 32] = header[..32].try_into().unwrap();
+    let leaf: [u8; 32] = header[32..64].try_into().unwrap();
+    let (steps, used) = decode_steps(&bytes[64..])?;
+    return Ok((SelfContainedProof { root, leaf, steps }, 64 + used));
+}
+
+fn encode_steps(steps: &[([u8; 32], Direction)], bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(&(steps.len() as u32).to_be_bytes());
+    for (sibling, direction) in steps {
+        bytes.push(match direction {
+            Direction::Left => 0,
+            Direction::Right => 1,
+        });
+        bytes.extend_from_slice(sibling);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn decode_steps(bytes: &[u8]) -> Result<(Vec<([u8; 32], Direction)>, usize), DecodeError> {
+    let header = bytes.get(..4).ok_or(DecodeError::Truncated)?;
+    let count = u32::from_be_bytes(header.try_into().unwrap()) as usize;
+    let end = count
+        .checked_mul(33)
+        .and_then(|len| len.checked_add(4))
+        .ok_or(DecodeError::Truncated)?;
+    let body = bytes.get(4..end).ok_or(DecodeError::Truncated)?;
+    let mut steps = Vec::with_capacity(count);
+    for step in body.chunks_exact(33) {
+        let direction = match step[0] {
+            0 => Direction::Left,
+            1 => Direction::Right,
+            byte => return Err(DecodeError::InvalidDirection { byte }),
+        };
+        steps.push((step[1..].try_into().unwrap(), direction));
+    }
+    return Ok((steps, end));
+}
+
+#[cfg(feature = "std")]
+pub fn verify_proof_from_reader<R: Read>(
+    leaf_hash: [u8; 32],
+    mut reader: R,
+    root: [u8; 32],
+) -> io::Result<bool> {
+    let mut current = leaf_hash;
+    let mut step = [0u8; 33];
+    loop {
+        let read = reader.read(&mut step[..1])?;
+        if read == 0 {
+            break;
+        }
+        reader.read_exact(&mut step[1..])?;
+        let direction = match step[0] {
+            0 => Direction::Left,
+            1 => Direction::Right,
+            byte => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    DecodeError::InvalidDirection { byte },
+                ));
+            }
+        };
+        current = fold_step(&current, step[1..].try_into().unwrap(), &direction);
+    }
+    return Ok(current == root);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256;
+    #[cfg(feature = "serde")]
+    mod serde_proof {
+        use super::*;
+        #[test]
+        fn json_round_trip_still_verifies() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let leaf = sha256(b"c");
+            let proof = Proof(tree.generate_proof(&leaf).unwrap());
+            let json = serde_json::to_string(&proof).unwrap();
+            let decoded: Proof = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, proof);
+            assert!(verify_proof(leaf, &decoded.0, *tree.get_root().unwrap()));
+        }
+        #[test]
+        fn json_uses_hex_siblings_and_direction_names() {
+            let proof = Proof(vec![([0xab; 32], Direction::Right)]);
+            let json = serde_json::to_string(&proof).unwrap();
+            assert_eq!(json, format!("[[\"{}\",\"Right\"]]", "ab".repeat(32)));
+        }
+        #[test]
+        fn malformed_siblings_are_rejected() {
+            let short = format!("[[\"{}\",\"Left\"]]", "ab".repeat(31));
+            assert!(serde_json::from_str::<Proof>(&short).is_err());
+            let not_hex = format!("[[\"{}\",\"Left\"]]", "zz".repeat(32));
+            assert!(serde_json::from_str::<Proof>(&not_hex).is_err());
+        }
+    }
+    mod proof_bytes {
+        use super::*;
+        #[test]
+        fn round_trips_proofs_of_several_depths() {
+            for count in [1, 2, 3, 8, 13] {
+                let values: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+                let tree = MerkleTree::from_bytes(&values);
+                for (index, value) in values.iter().enumerate() {
+                    let leaf = sha256(value.as_bytes());
+                    let proof = Proof(tree.generate_proof_by_index(index).unwrap());
+                    let bytes = proof.to_bytes();
+                    assert_eq!(bytes.len(), 4 + proof.0.len() * 33);
+                    let decoded = Proof::from_bytes(&bytes).unwrap();
+                    assert_eq!(decoded, proof);
+                    assert!(verify_proof(leaf, &decoded.0, *tree.get_root().unwrap()));
+                }
+            }
+        }
+        #[test]
+        fn single_leaf_proof_is_empty() {
+            let tree = MerkleTree::from_bytes(&["only"]);
+            let proof = Proof(tree.generate_proof_by_index(0).unwrap());
+            assert_eq!(proof.to_bytes(), vec![0, 0, 0, 0]);
+            assert_eq!(Proof::from_bytes(&[0, 0, 0, 0]), Ok(Proof(vec![])));
+        }
+        #[test]
+        fn malformed_bytes_are_rejected() {
+            let proof = Proof(vec![
+                ([7; 32], Direction::Left),
+                ([8; 32], Direction::Right),
+            ]);
+            let bytes = proof.to_bytes();
+            assert_eq!(
+                Proof::from_bytes(&bytes[..bytes.len() - 1]),
+                Err(DecodeError::Truncated)
+            );
+            assert_eq!(Proof::from_bytes(&[0, 0]), Err(DecodeError::Truncated));
+            let mut bad_direction = bytes.clone();
+            bad_direction[4 + 33] = 2;
+            assert_eq!(
+                Proof::from_bytes(&bad_direction),
+                Err(DecodeError::InvalidDirection { byte: 2 })
+            );
+            let mut trailing = bytes;
+            trailing.push(0);
+            assert_eq!(
+                Proof::from_bytes(&trailing),
+                Err(DecodeError::TrailingBytes { count: 1 })
+            );
+        }
+    }
+    mod signed_root {
+        use super::*;
+        fn mock_verify(root: &[u8; 32], signature: &[u8], pubkey: &[u8]) -> bool {
+            let mut expected = pubkey.to_vec();
+            expected.extend_from_slice(root);
+            return signature == sha256(&expected);
+        }
+        fn sign(root: [u8; 32], pubkey: &[u8]) -> SignedRoot {
+            let mut message = pubkey.to_vec();
+            message.extend_from_slice(&root);
+            return SignedRoot {
+                root,
+                signature: sha256(&message).to_vec(),
+            };
+        }
+        #[test]
+        fn valid_signature_and_proof_verify() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaf = sha256(b"b");
+            let proof = tree.generate_proof(&leaf).unwrap();
+            let signed = sign(*tree.get_root().unwrap(), b"key");
+            assert!(verify_proof_signed(
+                leaf,
+                &proof,
+                &signed,
+                b"key",
+                mock_verify
+            ));
+            assert!(!verify_proof_signed(
+                sha256(b"x"),
+                &proof,
+                &signed,
+                b"key",
+                mock_verify
+            ));
+        }
+        #[test]
+        fn bad_signature_is_rejected_before_inclusion_check() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaf = sha256(b"b");
+            let proof = tree.generate_proof(&leaf).unwrap();
+            let mut signed = sign(*tree.get_root().unwrap(), b"key");
+            signed.signature[0] ^= 1;
+            let mut calls = 0;
+            let verified = verify_proof_signed(leaf, &proof, &signed, b"key", |root, sig, key| {
+                calls += 1;
+                return mock_verify(root, sig, key);
+            });
+            assert!(!verified);
+            assert_eq!(calls, 1);
+            let signed = sign(*tree.get_root().unwrap(), b"key");
+            assert!(!verify_proof_signed(
+                leaf,
+                &proof,
+                &signed,
+                b"other",
+                mock_verify
+            ));
+        }
+    }
+    mod self_contained {
+        use super::*;
+        #[test]
+        fn proof_verifies_on_its_own() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            for index in 0..5 {
+                let proof = tree.self_contained_proof(index).unwrap();
+                assert_eq!(
+                    proof.leaf,
+                    sha256(["a", "b", "c", "d", "e"][index].as_bytes())
+                );
+                assert!(verify_self_contained(&proof));
+            }
+            assert!(tree.self_contained_proof(5).is_none());
+            let mut tampered = tree.self_contained_proof(2).unwrap();
+            tampered.root[0] ^= 1;
+            assert!(!verify_self_contained(&tampered));
+        }
+        #[test]
+        fn bytes_round_trip() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let proof = tree.self_contained_proof(2).unwrap();
+            let bytes = proof.to_bytes();
+            assert_eq!(bytes.len(), 68 + 2 * 33);
+            let decoded = SelfContainedProof::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, proof);
+            assert!(verify_self_contained(&decoded));
+        }
+        #[test]
+        fn malformed_bytes_are_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let mut bytes = tree.self_contained_proof(0).unwrap().to_bytes();
+            assert_eq!(
+                SelfContainedProof::from_bytes(&bytes[..bytes.len() - 1]),
+                Err(DecodeError::Truncated)
+            );
+            assert_eq!(
+                SelfContainedProof::from_bytes(&bytes[..40]),
+                Err(DecodeError::Truncated)
+            );
+            bytes.push(0);
+            assert_eq!(
+                SelfContainedProof::from_bytes(&bytes),
+                Err(DecodeError::TrailingBytes { count: 1 })
+            );
+            bytes.pop();
+            bytes[68] = 2;
+            assert_eq!(
+                SelfContainedProof::from_bytes(&bytes),
+                Err(DecodeError::InvalidDirection { byte: 2 })
+            );
+        }
+    }
+    mod aggregate {
+        use super::*;
+        fn bundle() -> AggregateProof {
+            let trees = [
+                MerkleTree::from_bytes(&["a", "b", "c"]),
+                MerkleTree::from_bytes(&["d"]),
+                MerkleTree::from_bytes(&["e", "f", "g", "h", "i"]),
+            ];
+            return AggregateProof {
+                entries: vec![
+                    trees[0].self_contained_proof(2).unwrap(),
+                    trees[1].self_contained_proof(0).unwrap(),
+                    trees[2].self_contained_proof(3).unwrap(),
+                ],
+            };
+        }
+        #[test]
+        fn tampered_entry_fails_while_others_verify() {
+            let mut bundle = bundle();
+            assert!(bundle.all_valid());
+            bundle.entries[2].steps[1].0[0] ^= 1;
+            assert!(!bundle.all_valid());
+            assert_eq!(verify_aggregate(&bundle), vec![true, true, false]);
+        }
+        #[test]
+        fn bundle_round_trips_through_bytes() {
+            let bundle = bundle();
+            let bytes = bundle.to_bytes();
+            assert_eq!(AggregateProof::from_bytes(&bytes), Ok(bundle));
+            assert_eq!(
+                AggregateProof::from_bytes(&bytes[..bytes.len() - 1]),
+                Err(DecodeError::Truncated)
+            );
+            let mut extended = bytes.clone();
+            extended.push(0);
+            assert_eq!(
+                AggregateProof::from_bytes(&extended),
+                Err(DecodeError::TrailingBytes { count: 1 })
+            );
+            let empty = AggregateProof::default();
+            assert_eq!(AggregateProof::from_bytes(&empty.to_bytes()), Ok(empty));
+        }
+    }
+    #[cfg(feature = "std")]
+    mod reader_verification {
+        use super::*;
+        use std::io::Cursor;
+        fn encoded_steps(proof: &SelfContainedProof) -> Vec<u8> {
+            return proof.to_bytes()[68..].to_vec();
+        }
+        #[test]
+        fn proof_read_from_cursor_verifies() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let proof = tree.self_contained_proof(3).unwrap();
+            let steps = encoded_steps(&proof);
+            assert_eq!(steps.len(), 3 * 33);
+            let verified = verify_proof_from_reader(proof.leaf, Cursor::new(&steps), proof.root);
+            assert!(verified.unwrap());
+            let other_leaf = sha256("a".as_bytes());
+            let verified = verify_proof_from_reader(other_leaf, Cursor::new(&steps), proof.root);
+            assert!(!verified.unwrap());
+        }
+        #[test]
+        fn malformed_streams_are_errors() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let proof = tree.self_contained_proof(0).unwrap();
+            let mut steps = encoded_steps(&proof);
+            steps.pop();
+            let result = verify_proof_from_reader(proof.leaf, Cursor::new(&steps), proof.root);
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+            steps.push(0);
+            steps[0] = 7;
+            let result = verify_proof_from_reader(proof.leaf, Cursor::new(&steps), proof.root);
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::hash::sha256;
+use crate::tree::MerkleTree;
+use alloc::vec::Vec;
+
+pub trait ProtoLeaf {
+    fn encode_leaf(&self) -> Vec<u8>;
+}
+
+#[cfg(feature = "prost")]
+impl<M: prost::Message> ProtoLeaf for M {
+    fn encode_leaf(&self) -> Vec<u8> {
+        return self.encode_to_vec();
+    }
+}
+
+impl MerkleTree {
+    pub fn from_proto<L: ProtoLeaf>(items: &[L]) -> Self {
+        let leaves = items
+            .iter()
+            .map(|item| sha256(&item.encode_leaf()))
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    mod manual_impl {
+        use super::*;
+        struct Entry {
+            id: u32,
+            payload: Vec<u8>,
+        }
+        impl ProtoLeaf for Entry {
+            fn encode_leaf(&self) -> Vec<u8> {
+                let mut bytes = self.id.to_be_bytes().to_vec();
+                bytes.extend_from_slice(&self.payload);
+                return bytes;
+            }
+        }
+        #[test]
+        fn leaves_are_hashes_of_encoded_items() {
+            let items = vec![
+                Entry {
+                    id: 1,
+                    payload: b"a".to_vec(),
+                },
+                Entry {
+                    id: 2,
+                    payload: b"b".to_vec(),
+                },
+            ];
+            let tree = MerkleTree::from_proto(&items);
+            let encoded: Vec<Vec<u8>> = items.iter().map(|i| i.encode_leaf()).collect();
+            assert_eq!(tree.get_root(), MerkleTree::from_bytes(&encoded).get_root());
+        }
+    }
+    #[cfg(feature = "prost")]
+    mod prost_messages {
+        use super::*;
+        #[derive(Clone, PartialEq, prost::Message)]
+        struct Transfer {
+            #[prost(string, tag = "1")]
+            from: String,
+            #[prost(uint64, tag = "2")]
+            amount: u64,
+        }
+        fn transfers() -> Vec<Transfer> {
+            return vec![
+                Transfer {
+                    from: "alice".to_string(),
+                    amount: 10,
+                },
+                Transfer {
+                    from: "bob".to_string(),
+                    amount: 20,
+                },
+            ];
+        }
+        #[test]
+        fn proto_tree_is_deterministic() {
+            let t1 = MerkleTree::from_proto(&transfers());
+            let t2 = MerkleTree::from_proto(&transfers());
+            assert_eq!(t1.get_root(), t2.get_root());
+        }
+        #[test]
+        fn proto_leaves_use_canonical_encoding() {
+            let items = transfers();
+            let tree = MerkleTree::from_proto(&items);
+            let leaf_hash = sha256(&prost::Message::encode_to_vec(&items[1]));
+            assert!(tree.generate_proof(&leaf_hash).is_some());
+            let mut changed = transfers();
+            changed[1].amount = 21;
+            assert_ne!(tree.get_root(), MerkleTree::from_proto(&changed).get_root());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::error::DecodeError;
+use crate::tree::{Direction, MerkleTree};
+use alloc::vec::Vec;
+
+impl MerkleTree {
+    pub fn generate_rlp_proof(&self, index: usize) -> Option<Vec<u8>> {
+        let proof = self.generate_proof_by_index(index)?;
+        let mut siblings = Vec::with_capacity(proof.len() * 33);
+        for (sibling, _) in &proof {
+            encode_string(&mut siblings, sibling);
+        }
+        let mut payload = Vec::with_capacity(siblings.len() + 12);
+        let index_bytes = (index as u64).to_be_bytes();
+        let leading_zeros = index_bytes.iter().take_while(|byte| **byte == 0).count();
+        encode_string(&mut payload, &index_bytes[leading_zeros..]);
+        encode_header(&mut payload, 0xc0, siblings.len());
+        payload.extend_from_slice(&siblings);
+        let mut encoded = Vec::with_capacity(payload.len() + 9);
+        encode_header(&mut encoded, 0xc0, payload.len());
+        encoded.extend_from_slice(&payload);
+        return Some(encoded);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn decode_rlp_proof(bytes: &[u8]) -> Result<(usize, Vec<([u8; 32], Direction)>), DecodeError> {
+    let (payload, rest) = decode_item(bytes, true)?;
+    if !rest.is_empty() {
+        return Err(DecodeError::TrailingBytes { count: rest.len() });
+    }
+    let (index_bytes, rest) = decode_item(payload, false)?;
+    let (mut siblings, rest) = decode_item(rest, true)?;
+    if !rest.is_empty() || index_bytes.first() == Some(&0) || index_bytes.len() > 8 {
+        return Err(DecodeError::InvalidRlp);
+    }
+    let index = index_bytes
+        .iter()
+        .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+    let index = usize::try_from(index).map_err(|_| DecodeError::InvalidRlp)?;
+    let mut proof = Vec::new();
+    while !siblings.is_empty() {
+        let (sibling, rest) = decode_item(siblings, false)?;
+        let sibling: [u8; 32] = sibling.try_into().map_err(|_| DecodeError::InvalidRlp)?;
+        let level = proof.len() as u32;
+        let direction = match index.checked_shr(level).unwrap_or(0) & 1 {
+            1 => Direction::Left,
+            _ => Direction::Right,
+        };
+        proof.push((sibling, direction));
+        siblings = rest;
+    }
+    return Ok((index, proof));
+}
+
+fn encode_string(out: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        out.push(bytes[0]);
+        return;
+    }
+    encode_header(out, 0x80, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn encode_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len <= 55 {
+        out.push(offset + len as u8);
+        return;
+    }
+    let len_bytes = (len as u64).to_be_bytes();
+    let leading_zeros = len_bytes.iter().take_while(|byte| **byte == 0).count();
+    out.push(offset + 55 + (8 - leading_zeros) as u8);
+    out.extend_from_slice(&len_bytes[leading_zeros..]);
+}
+
+fn decode_item(bytes: &[u8], expect_list: bool) -> Result<(&[u8], &[u8]), DecodeError> {
+    let prefix = *bytes.first().ok_or(DecodeError::Truncated)?;
+    let is_list = prefix >= 0xc0;
+    if is_list != expect_list {
+        return Err(DecodeError::InvalidRlp);
+    }
+    if prefix < 0x80 {
+        return Ok((&bytes[..1], &bytes[1..]));
+    }
+    let offset = if is_list { 0xc0 } else { 0x80 };
+    let (header_len, payload_len) = if prefix - offset <= 55 {
+        (1, (prefix - offset) as usize)
+    } else {
+        let len_of_len = (prefix - offset - 55) as usize;
+        let len_bytes = bytes.get(1..1 + len_of_len).ok_or(DecodeError::Truncated)?;
+        if len_bytes[0] == 0 {
+            return Err(DecodeError::InvalidRlp);
+        }
+        let len = len_bytes
+            .iter()
+            .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+        let len = usize::try_from(len).map_err(|_| DecodeError::InvalidRlp)?;
+        if len <= 55 {
+            return Err(DecodeError::InvalidRlp);
+        }
+        (1 + len_of_len, len)
+    };
+    let end = header_len
+        .checked_add(payload_len)
+        .ok_or(DecodeError::Truncated)?;
+    let payload = bytes.get(header_len..end).ok_or(DecodeError::Truncated)?;
+    if !is_list && payload_len == 1 && payload[0] < 0x80 {
+        return Err(DecodeError::InvalidRlp);
+    }
+    return Ok((payload, &bytes[end..]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256;
+    use crate::tree::verify_proof;
+    mod rlp_proofs {
+        use super::*;
+        #[test]
+        fn proofs_round_trip() {
+            let values: Vec<[u8; 1]> = (0..200u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            for index in [0, 1, 127, 128, 199] {
+                let encoded = tree.generate_rlp_proof(index).unwrap();
+                let (decoded_index, proof) = decode_rlp_proof(&encoded).unwrap();
+                assert_eq!(decoded_index, index);
+                assert_eq!(proof, tree.generate_proof_by_index(index).unwrap());
+            }
+            assert!(tree.generate_rlp_proof(200).is_none());
+        }
+        #[test]
+        fn decoded_proof_verifies() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let encoded = tree.generate_rlp_proof(3).unwrap();
+            let (_, proof) = decode_rlp_proof(&encoded).unwrap();
+            assert!(verify_proof(
+                sha256("d".as_bytes()),
+                &proof,
+                *tree.get_root().unwrap()
+            ));
+        }
+        #[test]
+        fn encoding_matches_rlp_layout() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let encoded = tree.generate_rlp_proof(1).unwrap();
+            let mut expected = vec![0xc0 + 35, 0x01, 0xc0 + 33, 0x80 + 32];
+            expected.extend_from_slice(&sha256("a".as_bytes()));
+            assert_eq!(encoded, expected);
+            let single = MerkleTree::from_bytes(&["a"])
+                .generate_rlp_proof(0)
+                .unwrap();
+            assert_eq!(single, vec![0xc2, 0x80, 0xc0]);
+        }
+        #[test]
+        fn malformed_encodings_are_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let mut encoded = tree.generate_rlp_proof(1).unwrap();
+            assert_eq!(
+                decode_rlp_proof(&encoded[..encoded.len() - 1]),
+                Err(DecodeError::Truncated)
+            );
+            encoded.push(0);
+            assert_eq!(
+                decode_rlp_proof(&encoded),
+                Err(DecodeError::TrailingBytes { count: 1 })
+            );
+            assert_eq!(
+                decode_rlp_proof(&[0xc3, 0x81, 0x01, 0xc0]),
+                Err(DecodeError::InvalidRlp)
+            );
+            assert_eq!(
+                decode_rlp_proof(&[0xc3, 0x01, 0xc1, 0x02]),
+                Err(DecodeError::InvalidRlp)
+            );
+            assert_eq!(decode_rlp_proof(&[0x80]), Err(DecodeError::InvalidRlp));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::hash::sha256;
+use crate::tree::hash_pair;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+pub const SMT_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmtProof {
+    pub siblings: Vec<Option<[u8; 32]>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree {
+    leaves: BTreeMap<[u8; 32], [u8; 32]>,
+    defaults: Vec<[u8; 32]>,
+}
+
+impl Default for SparseMerkleTree {
+    fn default() -> Self {
+        return SparseMerkleTree::new();
+    }
+}
+
+impl SparseMerkleTree {
+    pub fn new() -> Self {
+        return SparseMerkleTree {
+            leaves: BTreeMap::new(),
+            defaults: default_hashes(SMT_DEPTH),
+        };
+    }
+    pub fn len(&self) -> usize {
+        return self.leaves.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.leaves.is_empty();
+    }
+    pub fn insert(&mut self, key: [u8; 32], value: &[u8]) {
+        self.leaves.insert(key, sha256(value));
+    }
+    pub fn remove(&mut self, key: [u8; 32]) -> bool {
+        return self.leaves.remove(&key).is_some();
+    }
+    pub fn root(&self) -> [u8; 32] {
+        let entries: Vec<_> = self
+            .leaves
+            .iter()
+            .map(|(key, leaf)| (*key, *leaf))
+            .collect();
+        return self.subtree_root(SMT_DEPTH, &entries);
+    }
+    pub fn prove(&self, key: [u8; 32]) -> SmtProof {
+        let entries: Vec<_> = self
+            .leaves
+            .iter()
+            .map(|(key, leaf)| (*key, *leaf))
+            .collect();
+        let mut siblings = Vec::with_capacity(SMT_DEPTH);
+        let mut subtree = &entries[..];
+        for level in (1..=SMT_DEPTH).rev() {
+            let split = subtree.partition_point(|(other, _)| !key_bit(other, level - 1));
+            let (left, right) = subtree.split_at(split);
+            let (path, sibling) = if key_bit(&key, level - 1) {
+                (right, left)
+            } else {
+                (left, right)
+            };
+            siblings.push((!sibling.is_empty()).then(|| self.subtree_root(level - 1, sibling)));
+            subtree = path;
+        }
+        siblings.reverse();
+        return SmtProof { siblings };
+    }
+    fn subtree_root(&self, level: usize, entries: &[([u8; 32], [u8; 32])]) -> [u8; 32] {
+        if entries.is_empty() {
+            return self.defaults[level];
+        }
+        if level == 0 {
+            return entries[0].1;
+        }
+        let split = entries.partition_point(|(key, _)| !key_bit(key, level - 1));
+        let left = self.subtree_root(level - 1, &entries[..split]);
+        let right = self.subtree_root(level - 1, &entries[split..]);
+        return hash_pair(&left, &right);
+    }
+}
+
+pub fn verify(root: [u8; 32], key: [u8; 32], value: Option<&[u8]>, proof: &SmtProof) -> bool {
+    if proof.siblings.len() != SMT_DEPTH {
+        return false;
+    }
+    let defaults = default_hashes(SMT_DEPTH);
+    let mut current = value.map_or([0u8; 32], sha256);
+    for (level, sibling) in proof.siblings.iter().enumerate() {
+        let sibling = sibling.unwrap_or(defaults[level]);
+        current = if key_bit(&key, level) {
+            hash_pair(&sibling, &current)
+        } else {
+            hash_pair(&current, &sibling)
+        };
+    }
+    return current == root;
+}
+
+pub fn default_hashes(depth: usize) -> Vec<[u8; 32]> {
+    let mut defaults = Vec::with_capacity(depth + 1);
+    defaults.push([0u8; 32]);
+    for level in 0..depth {
+        let below = defaults[level];
+        defaults.push(hash_pair(&below, &below));
+    }
+    return defaults;
+}
+
+pub fn verify_sparse_proof(
+    leaf_hash: [u8; 32],
+    index: u64,
+    siblings: &[Option<[u8; 32]>],
+    root_hash: [u8; 32],
+) -> bool {
+    if siblings.len() > 64 || (siblings.len() < 64 && index >> siblings.len() != 0) {
+        return false;
+    }
+    let defaults = default_hashes(siblings.len());
+    let mut current = leaf_hash;
+    for (level, sibling) in siblings.iter().enumerate() {
+        let sibling = sibling.unwrap_or(defaults[level]);
+        current = if (index >> level) & 1 == 1 {
+            hash_pair(&sibling, &current)
+        } else {
+            hash_pair(&current, &sibling)
+        };
+    }
+    return current == root_hash;
+}
+
+fn key_bit(key: &[u8; 32], level: usize) -> bool {
+    return (key[31 - level / 8] >> (level % 8)) & 1 == 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MerkleTree;
+    use crate::hash::sha256;
+    mod default_hashes {
+        use super::*;
+        #[test]
+        fn defaults_match_empty_fixed_depth_trees() {
+            let defaults = default_hashes(4);
+            assert_eq!(defaults.len(), 5);
+            for (depth, default) in defaults.iter().enumerate() {
+                let empty = MerkleTree::from_bytes_fixed_depth::<&[u8]>(&[], depth as u32).unwrap();
+                assert_eq!(empty.get_root(), Some(default));
+            }
+        }
+    }
+    mod sparse_verification {
+        use super::*;
+        fn tree_with_leaf_at_5() -> (MerkleTree, [u8; 32]) {
+            let leaf = sha256("value".as_bytes());
+            let mut leaves = vec![[0u8; 32]; 8];
+            leaves[5] = leaf;
+            return (MerkleTree::from_leaf_hashes_vec(leaves), leaf);
+        }
+        #[test]
+        fn non_membership_uses_default_siblings() {
+            let (tree, _) = tree_with_leaf_at_5();
+            let root = *tree.get_root().unwrap();
+            let right_half = tree.level_nodes(2).unwrap()[1];
+            let siblings = [None, None, Some(right_half)];
+            assert!(verify_sparse_proof([0u8; 32], 2, &siblings, root));
+            assert!(!verify_sparse_proof(
+                sha256("value".as_bytes()),
+                2,
+                &siblings,
+                root
+            ));
+        }
+        #[test]
+        fn membership_and_out_of_range_index() {
+            let (tree, leaf) = tree_with_leaf_at_5();
+            let root = *tree.get_root().unwrap();
+            let left_half = tree.level_nodes(2).unwrap()[0];
+            let siblings = [None, None, Some(left_half)];
+            assert!(verify_sparse_proof(leaf, 5, &siblings, root));
+            assert!(!verify_sparse_proof(leaf, 13, &siblings, root));
+        }
+    }
+    mod sparse_merkle_tree {
+        use super::*;
+        fn key(last: u8) -> [u8; 32] {
+            let mut key = [0u8; 32];
+            key[31] = last;
+            return key;
+        }
+        #[test]
+        fn inclusion_proofs_verify() {
+            let mut smt = SparseMerkleTree::new();
+            smt.insert(key(1), b"one");
+            smt.insert(key(2), b"two");
+            smt.insert([0xff; 32], b"max");
+            let root = smt.root();
+            for (k, value) in [(key(1), b"one"), (key(2), b"two"), ([0xff; 32], b"max")] {
+                let proof = smt.prove(k);
+                assert!(verify(root, k, Some(value), &proof));
+                assert!(!verify(root, k, Some(b"bad"), &proof));
+                assert!(!verify(root, k, None, &proof));
+            }
+        }
+        #[test]
+        fn non_inclusion_proofs_verify() {
+            let mut smt = SparseMerkleTree::new();
+            assert!(verify(smt.root(), key(3), None, &smt.prove(key(3))));
+            smt.insert(key(1), b"one");
+            smt.insert([0x80; 32], b"high");
+            let root = smt.root();
+            for k in [key(0), key(3), [0x81; 32], [0x7f; 32]] {
+                let proof = smt.prove(k);
+                assert!(verify(root, k, None, &proof));
+                assert!(!verify(root, k, Some(b"one"), &proof));
+            }
+        }
+        #[test]
+        fn proofs_only_list_non_empty_siblings() {
+            let mut smt = SparseMerkleTree::new();
+            smt.insert(key(0), b"zero");
+            smt.insert(key(1), b"one");
+            let proof = smt.prove(key(0));
+            assert_eq!(proof.siblings.len(), SMT_DEPTH);
+            assert_eq!(proof.siblings[0], Some(sha256(b"one")));
+            assert!(proof.siblings[1..].iter().all(Option::is_none));
+        }
+        #[test]
+        fn insert_then_remove_restores_root() {
+            let mut smt = SparseMerkleTree::new();
+            let empty_root = smt.root();
+            assert_eq!(empty_root, default_hashes(SMT_DEPTH)[SMT_DEPTH]);
+            smt.insert(key(1), b"one");
+            let root = smt.root();
+            smt.insert(key(9), b"nine");
+            assert_ne!(smt.root(), root);
+            assert!(smt.remove(key(9)));
+            assert!(!smt.remove(key(9)));
+            assert_eq!(smt.root(), root);
+            smt.remove(key(1));
+            assert_eq!(smt.root(), empty_root);
+            assert!(smt.is_empty());
+        }
+        #[test]
+        fn overwriting_a_key_replaces_its_value() {
+            let mut smt = SparseMerkleTree::new();
+            smt.insert(key(4), b"old");
+            smt.insert(key(4), b"new");
+            assert_eq!(smt.len(), 1);
+            let proof = smt.prove(key(4));
+            assert!(verify(smt.root(), key(4), Some(b"new"), &proof));
+            assert!(!verify(smt.root(), key(4), Some(b"old"), &proof));
+        }
+    }
+}
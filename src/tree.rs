@@ -1,40 +1,633 @@
-use crate::hash::sha256;
+#[cfg(feature = "std")]
+use crate::error::HexError;
+use crate::error::{BuildError, IndexError, LengthError, MerkleError};
+use crate::hash::{
+    Endian, Hasher, HybridHasher, Sha256Hasher, commitment, kv_leaf_hash, leaf_hash_u64,
+    log_entry_hash, sha256, version_tagged_root,
+};
+#[cfg(feature = "std")]
+use crate::hex;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
 #[derive(Clone)]
-struct MerkleNode {
-    hash: [u8; 32],
-    left: Option<Box<MerkleNode>>,
-    right: Option<Box<MerkleNode>>,
+struct MerkleNode<const N: usize = 32> {
+    hash: [u8; N],
+    left: Option<Box<MerkleNode<N>>>,
+    right: Option<Box<MerkleNode<N>>>,
 }
 
-pub struct MerkleTree {
-    root: Option<MerkleNode>,
-    leaves: Vec<[u8; 32]>,
+#[derive(Clone)]
+pub struct MerkleTree<H = Sha256Hasher, const N: usize = 32> {
+    root: Option<MerkleNode<N>>,
+    pub(crate) leaves: Vec<[u8; N]>,
+    empty_root: Option<[u8; N]>,
+    pub(crate) padding: PaddingStrategy,
+    pub(crate) hasher: H,
+    layers: Option<Vec<Vec<[u8; N]>>>,
+    pub(crate) fixed_depth: Option<u32>,
+}
+
+impl<H: Hasher<N>, const N: usize> fmt::Debug for MerkleTree<H, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f
+            .debug_struct("MerkleTree")
+            .field("root", &self.get_root().map(|root| HexDigest(root)))
+            .field("leaf_count", &self.leaves.len())
+            .finish();
+    }
+}
+
+impl<H: Hasher<N>, const N: usize> PartialEq for MerkleTree<H, N> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.get_root() == other.get_root() && self.leaves == other.leaves;
+    }
+}
+
+impl<H: Hasher<N>, const N: usize> Eq for MerkleTree<H, N> {}
+
+impl<T: AsRef<[u8]>> FromIterator<T> for MerkleTree {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        return MerkleTree::from_bytes(&values);
+    }
+}
+
+impl<H, const N: usize> IntoIterator for MerkleTree<H, N> {
+    type Item = [u8; N];
+    type IntoIter = vec::IntoIter<[u8; N]>;
+    fn into_iter(self) -> Self::IntoIter {
+        return self.leaves.into_iter();
+    }
+}
+
+impl<'a, H, const N: usize> IntoIterator for &'a MerkleTree<H, N> {
+    type Item = &'a [u8; N];
+    type IntoIter = core::slice::Iter<'a, [u8; N]>;
+    fn into_iter(self) -> Self::IntoIter {
+        return self.leaves.iter();
+    }
+}
+
+struct HexDigest<'a>(&'a [u8]);
+
+impl fmt::Debug for HexDigest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        return Ok(());
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyRootMode {
+    #[default]
+    None,
+    Canonical,
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaddingStrategy {
+    #[default]
+    Duplicate,
+    Promote,
+}
+
+pub const MAX_BITPATH_DEPTH: usize = 64;
+
+#[cfg(feature = "std")]
+const ASCII_ART_MAX_LEAVES: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Left,
     Right,
 }
 
-impl MerkleTree {
-    pub fn get_root(&self) -> Option<&[u8; 32]> {
-        return self.root.as_ref().map(|n| &n.hash);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeStats {
+    pub leaf_count: usize,
+    pub depth: usize,
+    pub internal_node_count: usize,
+    pub is_perfect: bool,
+    pub padding_count: usize,
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerificationReceipt {
+    pub verified_at: std::time::SystemTime,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],
+    pub steps: usize,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct TreeJson {
+    root: Option<String>,
+    leaf_count: usize,
+    leaves: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint<const N: usize = 32> {
+    leaf_count: usize,
+    root: Option<[u8; N]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofStepRange<const N: usize = 32> {
+    pub sibling: [u8; N],
+    pub direction: Direction,
+    pub covers: (usize, usize),
+}
+
+pub struct ProofCache<const N: usize = 32> {
+    levels: Vec<Vec<[u8; N]>>,
+    padding: PaddingStrategy,
+}
+
+impl<const N: usize> ProofCache<N> {
+    pub fn proof(&self, index: usize) -> Option<Vec<([u8; N], Direction)>> {
+        return proof_from_levels(&self.levels, index, self.padding);
     }
-    pub fn from_bytes<T: AsRef<[u8]>>(values: &[T]) -> Self {
-        let leaves = build_leaves_array(values);
+}
+
+#[cfg(feature = "std")]
+pub struct MembershipIndex<const N: usize = 32> {
+    leaves: HashSet<[u8; N]>,
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> MembershipIndex<N> {
+    pub fn contains(&self, leaf_hash: &[u8; N]) -> bool {
+        return self.leaves.contains(leaf_hash);
+    }
+    pub fn len(&self) -> usize {
+        return self.leaves.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.leaves.is_empty();
+    }
+}
+
+impl<H: Hasher<N>, const N: usize> MerkleTree<H, N> {
+    pub fn get_root(&self) -> Option<&[u8; N]> {
+        if let Some(layers) = &self.layers {
+            return layers
+                .last()
+                .map(|level| &level[0])
+                .or(self.empty_root.as_ref());
+        }
+        return match &self.root {
+            Some(root) => Some(&root.hash),
+            None => self.empty_root.as_ref(),
+        };
+    }
+    pub fn leaves(&self) -> &[[u8; N]] {
+        return &self.leaves;
+    }
+    pub fn contains(&self, leaf_hash: &[u8; N]) -> bool {
+        return self.leaves.contains(leaf_hash);
+    }
+    pub fn index_of(&self, leaf_hash: &[u8; N]) -> Option<usize> {
+        return self.leaves.iter().position(|leaf| leaf == leaf_hash);
+    }
+    pub fn get_leaf(&self, index: usize) -> Option<&[u8; N]> {
+        return self.leaves.get(index);
+    }
+    pub fn iter_leaves(&self) -> impl Iterator<Item = &[u8; N]> {
+        return self.leaves.iter();
+    }
+    pub fn len(&self) -> usize {
+        return self.leaves.len();
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.leaves.is_empty();
+    }
+    pub fn depth(&self) -> usize {
+        if let Some(layers) = &self.layers {
+            return layers.len();
+        }
+        if self.leaves.is_empty() {
+            return 0;
+        }
+        return (usize::BITS - (self.leaves.len() - 1).leading_zeros()) as usize + 1;
+    }
+    pub fn with_empty_root_mode(mut self, mode: EmptyRootMode) -> Self {
+        self.empty_root = match mode {
+            EmptyRootMode::None => None,
+            EmptyRootMode::Canonical => Some(self.hasher.digest(&[])),
+        };
+        return self;
+    }
+    pub fn with_padding_strategy(mut self, padding: PaddingStrategy) -> Self {
+        if self.padding != padding {
+            self.padding = padding;
+            self.rebuild();
+        }
+        return self;
+    }
+    pub fn from_bytes_with<T: AsRef<[u8]>>(values: &[T], hasher: H) -> Self {
+        let leaves = build_leaves_array(values, &hasher);
         let root = if !leaves.is_empty() {
-            Some(build_merkle_tree_recursively(&leaves))
+            Some(build_merkle_tree_recursively(
+                &leaves,
+                &hasher,
+                PaddingStrategy::Duplicate,
+            ))
         } else {
             None
         };
         return MerkleTree {
             root,
             leaves: leaves.iter().map(|n| n.hash).collect(),
+            empty_root: None,
+            padding: PaddingStrategy::Duplicate,
+            hasher,
+            layers: None,
+            fixed_depth: None,
         };
     }
+    pub fn padding_strategy(&self) -> PaddingStrategy {
+        return self.padding;
+    }
     pub fn push(&mut self, value: &[u8]) {
-        let leaf_hashed = sha256(value);
+        let _ = self.try_push(value);
+    }
+    pub fn try_push(&mut self, value: &[u8]) -> Result<(), BuildError> {
+        self.check_fixed_capacity(1)?;
+        let leaf_hashed = self.hasher.hash_leaf(value);
         self.leaves.push(leaf_hashed);
-        let nodes: Vec<MerkleNode> = self
+        if self.fixed_depth.is_none()
+            && let Some(layers) = &mut self.layers
+        {
+            match layers.first_mut() {
+                Some(level) => level.push(leaf_hashed),
+                None => layers.push(vec![leaf_hashed]),
+            }
+            refresh_path(layers, self.leaves.len() - 1, &self.hasher, self.padding);
+            return Ok(());
+        }
+        self.rebuild();
+        return Ok(());
+    }
+    pub fn extend<T: AsRef<[u8]>>(&mut self, values: &[T]) {
+        let _ = self.try_extend(values);
+    }
+    pub fn try_extend<T: AsRef<[u8]>>(&mut self, values: &[T]) -> Result<(), BuildError> {
+        self.check_fixed_capacity(values.len())?;
+        for value in values {
+            let leaf_hashed = self.hasher.hash_leaf(value.as_ref());
+            self.leaves.push(leaf_hashed);
+        }
+        self.rebuild();
+        return Ok(());
+    }
+    fn check_fixed_capacity(&self, additional: usize) -> Result<(), BuildError> {
+        let Some(depth) = self.fixed_depth else {
+            return Ok(());
+        };
+        let leaf_count = self.leaves.len().saturating_add(additional);
+        if leaf_count > 1 << depth {
+            return Err(BuildError::TooDeep { leaf_count, depth });
+        }
+        return Ok(());
+    }
+    pub fn update(&mut self, index: usize, new_value: &[u8]) -> Result<(), IndexError> {
+        let len = self.leaves.len();
+        if index >= len {
+            return Err(IndexError { index, len });
+        }
+        let leaf_hash = self.hasher.hash_leaf(new_value);
+        self.leaves[index] = leaf_hash;
+        if self.fixed_depth.is_some() {
+            self.rebuild();
+            return Ok(());
+        }
+        if let Some(layers) = &mut self.layers {
+            layers[0][index] = leaf_hash;
+            refresh_path(layers, index, &self.hasher, self.padding);
+            return Ok(());
+        }
+        if self.padding == PaddingStrategy::Promote {
+            self.rebuild();
+            return Ok(());
+        }
+        let root_level = self.depth() - 1;
+        let root = self.root.as_mut().expect("non-empty tree has a root");
+        update_path(root, root_level, index, len, leaf_hash, &self.hasher);
+        return Ok(());
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn update_and_diff(
+        &mut self,
+        index: usize,
+        value: &[u8],
+    ) -> Option<(([u8; N], [u8; N]), Vec<([u8; N], Direction)>)> {
+        let old_root = *self.get_root()?;
+        self.update(index, value).ok()?;
+        let new_root = *self.get_root()?;
+        let proof = self.generate_proof_by_index(index)?;
+        return Some(((old_root, new_root), proof));
+    }
+    pub fn push_checkpoint(&mut self, value: &[u8]) -> Checkpoint<N> {
+        let checkpoint = Checkpoint {
+            leaf_count: self.leaves.len(),
+            root: self.get_root().copied(),
+        };
+        self.push(value);
+        return checkpoint;
+    }
+    pub fn rollback(&mut self, cp: Checkpoint<N>) -> bool {
+        if cp.leaf_count > self.leaves.len() {
+            return false;
+        }
+        let dropped = self.leaves.split_off(cp.leaf_count);
+        self.rebuild();
+        if self.get_root().copied() != cp.root {
+            self.leaves.extend(dropped);
+            self.rebuild();
+            return false;
+        }
+        return true;
+    }
+    pub fn clear(&mut self) {
+        self.leaves.clear();
+        self.root = None;
+        if self.layers.is_some() {
+            self.rebuild();
+        }
+    }
+    pub fn generate_proof(&self, target: &[u8; N]) -> Option<Vec<([u8; N], Direction)>> {
+        if let Some(layers) = &self.layers {
+            return proof_from_levels(layers, self.index_of(target)?, self.padding);
+        }
+        let root = self.root.as_ref()?;
+        let mut proof = Vec::new();
+        if dfs_generate_proof(root, target, &mut proof) {
+            return Some(proof);
+        } else {
+            return None;
+        }
+    }
+    pub fn try_generate_proof(
+        &self,
+        target: &[u8; N],
+    ) -> Result<Vec<([u8; N], Direction)>, MerkleError> {
+        if self.leaves.is_empty() {
+            return Err(MerkleError::EmptyTree);
+        }
+        return self.generate_proof(target).ok_or(MerkleError::LeafNotFound);
+    }
+    pub fn try_get_leaf(&self, index: usize) -> Result<[u8; N], MerkleError> {
+        if self.leaves.is_empty() {
+            return Err(MerkleError::EmptyTree);
+        }
+        return self
+            .leaves
+            .get(index)
+            .copied()
+            .ok_or(MerkleError::IndexOutOfRange {
+                index,
+                len: self.leaves.len(),
+            });
+    }
+    pub fn generate_proof_by_index(&self, index: usize) -> Option<Vec<([u8; N], Direction)>> {
+        if let Some(layers) = &self.layers {
+            if index >= self.leaves.len() {
+                return None;
+            }
+            return proof_from_levels(layers, index, self.padding);
+        }
+        let levels = build_levels(&self.leaves, &self.hasher, self.padding);
+        return proof_from_levels(&levels, index, self.padding);
+    }
+    pub fn generate_proof_bools(&self, index: usize) -> Option<Vec<([u8; N], bool)>> {
+        let proof = self.generate_proof_by_index(index)?;
+        return Some(
+            proof
+                .into_iter()
+                .map(|(sibling, direction)| (sibling, direction == Direction::Right))
+                .collect(),
+        );
+    }
+    pub fn auth_path_bytes(&self, index: usize) -> Option<Vec<u8>> {
+        let proof = self.generate_proof_by_index(index)?;
+        return Some(proof.iter().flat_map(|(sibling, _)| *sibling).collect());
+    }
+    pub fn generate_proof_annotated_ranges(&self, index: usize) -> Option<Vec<ProofStepRange<N>>> {
+        let proof = self.generate_proof_by_index(index)?;
+        let leaf_count = self.leaves.len();
+        let mut steps = Vec::with_capacity(proof.len());
+        let mut proof = proof.into_iter();
+        for level in 0..self.depth() - 1 {
+            let position = index >> level;
+            let level_len = leaf_count.div_ceil(1 << level);
+            if self.fixed_depth.is_none()
+                && self.padding == PaddingStrategy::Promote
+                && position.is_multiple_of(2)
+                && position + 1 >= level_len
+            {
+                continue;
+            }
+            let (sibling, direction) = proof.next().expect("one proof step per level");
+            let sibling_position = match direction {
+                Direction::Left => position - 1,
+                Direction::Right if position + 1 < level_len || self.fixed_depth.is_some() => {
+                    position + 1
+                }
+                Direction::Right => position,
+            };
+            let start = (sibling_position << level).min(leaf_count);
+            let end = ((sibling_position + 1) << level).min(leaf_count);
+            steps.push(ProofStepRange {
+                sibling,
+                direction,
+                covers: (start, end),
+            });
+        }
+        return Some(steps);
+    }
+    pub fn generate_bitpath_proof(&self, index: usize) -> Option<(u64, Vec<[u8; N]>)> {
+        let proof = self.generate_proof_by_index(index)?;
+        if proof.len() > MAX_BITPATH_DEPTH {
+            return None;
+        }
+        let mut bitpath = 0u64;
+        let mut siblings = Vec::with_capacity(proof.len());
+        for (level, (sibling_hash, direction)) in proof.iter().enumerate() {
+            if *direction == Direction::Left {
+                bitpath |= 1 << level;
+            }
+            siblings.push(*sibling_hash);
+        }
+        return Some((bitpath, siblings));
+    }
+    pub fn level_nodes(&self, level: usize) -> Option<Vec<[u8; N]>> {
+        let mut levels = self.levels();
+        if level >= levels.len() {
+            return None;
+        }
+        return Some(levels.swap_remove(level));
+    }
+    pub fn build_proof_cache(&self) -> ProofCache<N> {
+        return ProofCache {
+            levels: self.levels(),
+            padding: self.padding,
+        };
+    }
+    #[cfg(feature = "std")]
+    pub fn build_membership_index(&self) -> MembershipIndex<N> {
+        return MembershipIndex {
+            leaves: self.leaves.iter().copied().collect(),
+        };
+    }
+    pub fn into_verifier(self) -> impl Fn(&[u8]) -> bool {
+        let mut leaves = self.leaves;
+        leaves.sort_unstable();
+        leaves.dedup();
+        let hasher = self.hasher;
+        return move |value| leaves.binary_search(&hasher.hash_leaf(value)).is_ok();
+    }
+    pub fn proofs_iter(&self) -> impl Iterator<Item = Vec<([u8; N], Direction)>> {
+        let cache = self.build_proof_cache();
+        return (0..self.leaves.len()).filter_map(move |index| cache.proof(index));
+    }
+    pub fn stats(&self) -> TreeStats {
+        let leaf_count = self.leaves.len();
+        let mut stats = TreeStats {
+            leaf_count,
+            depth: 0,
+            internal_node_count: 0,
+            is_perfect: leaf_count.is_power_of_two(),
+            padding_count: 0,
+        };
+        if let Some(depth) = self.fixed_depth {
+            let mut level_size = leaf_count;
+            for _ in 0..depth {
+                if level_size % 2 == 1 {
+                    stats.padding_count += 1;
+                }
+                level_size = level_size.div_ceil(2);
+                stats.internal_node_count += level_size;
+            }
+            stats.depth = depth as usize + 1;
+            stats.internal_node_count = stats.internal_node_count.max(1);
+            stats.is_perfect = leaf_count == 1 << depth;
+            return stats;
+        }
+        if leaf_count == 0 {
+            return stats;
+        }
+        let mut level_size = leaf_count;
+        stats.depth = 1;
+        while level_size > 1 {
+            let promoted = level_size % 2 == 1 && self.padding == PaddingStrategy::Promote;
+            if level_size % 2 == 1 && !promoted {
+                stats.padding_count += 1;
+            }
+            level_size = level_size.div_ceil(2);
+            stats.internal_node_count += level_size - usize::from(promoted);
+            stats.depth += 1;
+        }
+        return stats;
+    }
+    #[cfg(feature = "std")]
+    pub fn to_ascii_art(&self) -> String {
+        if self.leaves.is_empty() {
+            return String::from("(empty tree)\n");
+        }
+        let levels = self.levels();
+        let root = &levels[levels.len() - 1][0];
+        if self.leaves.len() > ASCII_ART_MAX_LEAVES {
+            return format!(
+                "tree of {} leaves, root {} (too large to draw)\n",
+                self.leaves.len(),
+                short_hex(root)
+            );
+        }
+        let mut art = format!("root {}\n", short_hex(root));
+        draw_children(&levels, levels.len() - 1, 0, self.padding, "", &mut art);
+        return art;
+    }
+    #[cfg(feature = "std")]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph merkle {\n");
+        let rebuilt = self
+            .layers
+            .as_ref()
+            .filter(|layers| !self.leaves.is_empty() && !layers.is_empty())
+            .map(|layers| node_from_levels(layers, layers.len() - 1, 0, self.padding));
+        if let Some(root) = rebuilt.as_ref().or(self.root.as_ref()) {
+            let mut next_id = 0;
+            write_dot_node(root, &mut next_id, &mut dot);
+        }
+        dot.push_str("}\n");
+        return dot;
+    }
+    #[cfg(feature = "std")]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("index,leaf_hash_hex\n");
+        for (index, leaf) in self.leaves.iter().enumerate() {
+            csv.push_str(&format!("{},{}\n", index, hex::encode(leaf)));
+        }
+        if let Some(root) = self.get_root() {
+            csv.push_str(&format!("# root,{}\n", hex::encode(root)));
+        }
+        return csv;
+    }
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let tree = TreeJson {
+            root: self.get_root().map(|root| hex::encode(root)),
+            leaf_count: self.leaves.len(),
+            leaves: self.leaves.iter().map(|leaf| hex::encode(leaf)).collect(),
+        };
+        return serde_json::to_string(&tree).expect("tree JSON is always serializable");
+    }
+    pub(crate) fn from_leaf_hashes_with(leaves: Vec<[u8; N]>, hasher: H) -> Self {
+        let mut tree = MerkleTree {
+            root: None,
+            leaves,
+            empty_root: None,
+            padding: PaddingStrategy::Duplicate,
+            hasher,
+            layers: None,
+            fixed_depth: None,
+        };
+        tree.rebuild();
+        return tree;
+    }
+    fn levels(&self) -> Vec<Vec<[u8; N]>> {
+        return match &self.layers {
+            Some(layers) => layers.clone(),
+            None => build_levels(&self.leaves, &self.hasher, self.padding),
+        };
+    }
+    fn rebuild(&mut self) {
+        if let Some(depth) = self.fixed_depth {
+            self.layers = Some(build_fixed_levels(&self.leaves, &self.hasher, depth));
+            return;
+        }
+        if self.layers.is_some() {
+            self.layers = Some(build_levels(&self.leaves, &self.hasher, self.padding));
+            return;
+        }
+        return self.root = self.build_root();
+    }
+    fn build_root(&self) -> Option<MerkleNode<N>> {
+        let nodes: Vec<MerkleNode<N>> = self
             .leaves
             .iter()
             .map(|h| MerkleNode {
@@ -43,21 +636,199 @@ impl MerkleTree {
                 right: None,
             })
             .collect();
-        let new_tree = if nodes.is_empty() {
-            None
+        if nodes.is_empty() {
+            return None;
+        }
+        return Some(build_merkle_tree_recursively(
+            &nodes,
+            &self.hasher,
+            self.padding,
+        ));
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<H: Hasher<N> + Sync, const N: usize> MerkleTree<H, N> {
+    pub fn from_bytes_par_with<T: AsRef<[u8]> + Sync>(values: &[T], hasher: H) -> Self {
+        let leaves: Vec<MerkleNode<N>> = values
+            .par_iter()
+            .map(|value| MerkleNode {
+                hash: hasher.hash_leaf(value.as_ref()),
+                left: None,
+                right: None,
+            })
+            .collect();
+        let leaf_hashes = leaves.iter().map(|n| n.hash).collect();
+        let root = if !leaves.is_empty() {
+            Some(build_merkle_tree_par(leaves, &hasher))
         } else {
-            Some(build_merkle_tree_recursively(&nodes))
+            None
+        };
+        return MerkleTree {
+            root,
+            leaves: leaf_hashes,
+            empty_root: None,
+            padding: PaddingStrategy::Duplicate,
+            hasher,
+            layers: None,
+            fixed_depth: None,
         };
-        return self.root = new_tree;
     }
-    pub fn generate_proof(&self, target: &[u8; 32]) -> Option<Vec<([u8; 32], Direction)>> {
-        let root = self.root.as_ref()?;
-        let mut proof = Vec::new();
-        if dfs_generate_proof(root, target, &mut proof) {
-            return Some(proof);
-        } else {
-            return None;
+}
+
+impl MerkleTree {
+    pub fn empty_root() -> [u8; 32] {
+        return sha256(&[]);
+    }
+    pub fn with_incremental() -> Self {
+        let mut tree = MerkleTree::from_leaf_hashes_vec(Vec::new());
+        tree.layers = Some(Vec::new());
+        return tree;
+    }
+    pub fn from_bytes<T: AsRef<[u8]>>(values: &[T]) -> Self {
+        return MerkleTree::from_bytes_with(values, Sha256Hasher);
+    }
+    #[cfg(feature = "rayon")]
+    pub fn from_bytes_par<T: AsRef<[u8]> + Sync>(values: &[T]) -> Self {
+        return MerkleTree::from_bytes_par_with(values, Sha256Hasher);
+    }
+    pub fn from_bytes_with_padding<T: AsRef<[u8]>>(values: &[T], padding: PaddingStrategy) -> Self {
+        return MerkleTree::from_bytes(values).with_padding_strategy(padding);
+    }
+    #[cfg(feature = "std")]
+    pub fn from_lines<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut leaves = Vec::new();
+        for line in reader.split(b'\n') {
+            let mut line = line?;
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            leaves.push(sha256(&line));
+        }
+        return Ok(MerkleTree::from_leaf_hashes_vec(leaves));
+    }
+    pub fn from_chunked_values(values: &[Vec<Vec<u8>>]) -> Self {
+        let leaves = values
+            .iter()
+            .map(|chunks| match MerkleTree::from_bytes(chunks).get_root() {
+                Some(root) => *root,
+                None => [0u8; 32],
+            })
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+    pub fn from_bytes_fixed_depth<T: AsRef<[u8]>>(
+        values: &[T],
+        depth: u32,
+    ) -> Result<Self, BuildError> {
+        let capacity = 1usize
+            .checked_shl(depth)
+            .ok_or(BuildError::DepthOverflow { depth })?;
+        if values.len() > capacity {
+            return Err(BuildError::TooDeep {
+                leaf_count: values.len(),
+                depth,
+            });
+        }
+        let leaves = values.iter().map(|value| sha256(value.as_ref())).collect();
+        let mut tree = MerkleTree::from_leaf_hashes_vec(Vec::new());
+        tree.leaves = leaves;
+        tree.fixed_depth = Some(depth);
+        tree.rebuild();
+        return Ok(tree);
+    }
+    pub fn from_single(value: &[u8]) -> Self {
+        return MerkleTree::from_leaf_hashes_vec(vec![sha256(value)]);
+    }
+    pub fn from_u64s(values: &[u64], endian: Endian) -> Self {
+        let leaves = values
+            .iter()
+            .map(|value| leaf_hash_u64(*value, endian))
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+    pub fn from_commitments(values_and_nonces: &[(&[u8], &[u8; 32])]) -> Self {
+        let leaves = values_and_nonces
+            .iter()
+            .map(|(value, nonce)| commitment(value, nonce))
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+    pub fn from_kv_pairs(pairs: &[(&[u8], &[u8])]) -> Self {
+        let leaves = pairs
+            .iter()
+            .map(|(key, value)| kv_leaf_hash(key, value))
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+    pub fn from_hashes(hashes: &[[u8; 32]]) -> Self {
+        return MerkleTree::from_leaf_hashes_vec(hashes.to_vec());
+    }
+    pub fn from_log_entries(entries: &[&[u8]]) -> Self {
+        let leaves = entries
+            .iter()
+            .enumerate()
+            .map(|(seq, entry)| log_entry_hash(seq as u64, entry))
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+    pub fn from_bytes_versioned<T: AsRef<[u8]>>(
+        values: &[T],
+        version: u32,
+    ) -> (Self, Option<[u8; 32]>) {
+        let tree = MerkleTree::from_bytes(values);
+        let tagged_root = tree
+            .get_root()
+            .map(|root| version_tagged_root(version, root));
+        return (tree, tagged_root);
+    }
+    pub fn from_iter_leaf_hashes<I: IntoIterator<Item = [u8; 32]>>(iter: I) -> Self {
+        return MerkleTree::from_leaf_hashes_vec(iter.into_iter().collect());
+    }
+    pub fn from_bytes_canonical<T: AsRef<[u8]>>(values: &[T]) -> Self {
+        let mut leaves: Vec<[u8; 32]> = values.iter().map(|value| sha256(value.as_ref())).collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+        return MerkleTree::from_leaf_hashes_vec(leaves);
+    }
+    pub fn from_sorted_leaf_hashes_unchecked(hashes: &[[u8; 32]]) -> Self {
+        debug_assert!(
+            hashes.windows(2).all(|pair| pair[0] < pair[1]),
+            "leaf hashes must be sorted and unique"
+        );
+        return MerkleTree::from_leaf_hashes_vec(hashes.to_vec());
+    }
+    pub fn level_commitment(&self, level: usize) -> Option<[u8; 32]> {
+        let nodes = self.level_nodes(level)?;
+        return Some(sha256(&nodes.concat()));
+    }
+    pub fn fingerprint(&self) -> u64 {
+        let mut data = Vec::with_capacity(40);
+        if let Some(root) = self.get_root() {
+            data.extend_from_slice(root);
         }
+        data.extend_from_slice(&(self.leaves.len() as u64).to_le_bytes());
+        let digest = sha256(&data);
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&digest[..8]);
+        return u64::from_le_bytes(prefix);
+    }
+    pub fn filtered_root<F: Fn(usize, &[u8; 32]) -> bool>(&self, keep: F) -> Option<[u8; 32]> {
+        let leaves: Vec<[u8; 32]> = self
+            .leaves
+            .iter()
+            .enumerate()
+            .filter(|(index, leaf)| keep(*index, leaf))
+            .map(|(_, leaf)| *leaf)
+            .collect();
+        return MerkleTree::from_leaf_hashes_vec(leaves).get_root().copied();
+    }
+    #[allow(clippy::type_complexity)]
+    pub fn verifier_for(root: [u8; 32]) -> impl Fn(&[u8; 32], &[([u8; 32], Direction)]) -> bool {
+        return move |leaf, proof| verify_proof(*leaf, proof, root);
+    }
+    pub(crate) fn from_leaf_hashes_vec(leaves: Vec<[u8; 32]>) -> Self {
+        return MerkleTree::from_leaf_hashes_with(leaves, Sha256Hasher);
     }
 }
 
@@ -66,58 +837,535 @@ pub fn verify_proof(
     proof: &[([u8; 32], Direction)],
     root_hash: [u8; 32],
 ) -> bool {
+    return verify_proof_with(&Sha256Hasher, leaf_hash, proof, root_hash);
+}
+
+pub fn verify_proof_with<H: Hasher<N>, const N: usize>(
+    hasher: &H,
+    leaf_hash: [u8; N],
+    proof: &[([u8; N], Direction)],
+    root_hash: [u8; N],
+) -> bool {
+    if proof.is_empty() {
+        return leaf_hash == root_hash;
+    }
     let mut current = leaf_hash;
     for (sibling_hash, direction) in proof {
-        let mut data = Vec::with_capacity(current.len() + sibling_hash.len());
-        match direction {
-            Direction::Left => {
-                data.extend_from_slice(sibling_hash);
-                data.extend_from_slice(&current);
-            }
-            Direction::Right => {
-                data.extend_from_slice(&current);
-                data.extend_from_slice(sibling_hash);
-            }
-        }
-        current = sha256(&data);
+        current = fold_step_with(hasher, &current, sibling_hash, direction);
     }
     return current == root_hash;
 }
 
-fn build_leaves_array<T: AsRef<[u8]>>(values: &[T]) -> Vec<MerkleNode> {
-    return values
-        .iter()
-        .map(|value| {
-            let hash = sha256(value.as_ref());
-            MerkleNode {
-                hash,
-                left: None,
-                right: None,
-            }
-        })
-        .collect();
+pub fn verify_proof_with_index(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> Option<usize> {
+    if !verify_proof(leaf_hash, proof, root_hash) {
+        return None;
+    }
+    return index_from_directions(proof);
 }
 
-fn build_merkle_tree_recursively(nodes: &[MerkleNode]) -> MerkleNode {
-    if nodes.len() == 1 {
-        return nodes[0].clone();
+pub fn verify_proof_hybrid<L: Hasher<N>, R: Hasher<N>, const N: usize>(
+    hasher: &HybridHasher<L, R>,
+    leaf_hash: [u8; N],
+    proof: &[([u8; N], Direction)],
+    root_hash: [u8; N],
+) -> bool {
+    return verify_proof_with(hasher, leaf_hash, proof, root_hash);
+}
+
+#[cfg(feature = "std")]
+pub fn verify_with_receipt(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> Option<VerificationReceipt> {
+    if !verify_proof(leaf_hash, proof, root_hash) {
+        return None;
     }
-    let mut parents = Vec::new();
-    let mut i: usize = 0;
+    return Some(VerificationReceipt {
+        verified_at: std::time::SystemTime::now(),
+        leaf: leaf_hash,
+        root: root_hash,
+        steps: proof.len(),
+    });
+}
 
-    while i < nodes.len() {
-        let left = nodes[i].clone();
-        let right = if i + 1 < nodes.len() {
-            nodes[i + 1].clone()
-        } else {
-            nodes[i].clone()
+pub fn verify_root_from_leaves(leaves: &[[u8; 32]], root_hash: [u8; 32]) -> bool {
+    let tree = MerkleTree::from_leaf_hashes_vec(leaves.to_vec());
+    return tree.get_root() == Some(&root_hash);
+}
+
+pub fn verify_proof_slice(
+    leaf_hash: &[u8],
+    proof: &[([u8; 32], Direction)],
+    root: &[u8],
+) -> Result<bool, LengthError> {
+    let leaf_hash: [u8; 32] = leaf_hash.try_into().map_err(|_| LengthError::Leaf {
+        len: leaf_hash.len(),
+    })?;
+    let root: [u8; 32] = root
+        .try_into()
+        .map_err(|_| LengthError::Root { len: root.len() })?;
+    return Ok(verify_proof(leaf_hash, proof, root));
+}
+
+pub fn verify_bitpath_proof(
+    leaf_hash: [u8; 32],
+    bitpath: u64,
+    siblings: &[[u8; 32]],
+    root_hash: [u8; 32],
+) -> bool {
+    if siblings.len() > MAX_BITPATH_DEPTH
+        || bitpath.checked_shr(siblings.len() as u32).unwrap_or(0) != 0
+    {
+        return false;
+    }
+    let mut current = leaf_hash;
+    for (level, sibling_hash) in siblings.iter().enumerate() {
+        let direction = if (bitpath >> level) & 1 == 1 {
+            Direction::Left
+        } else {
+            Direction::Right
         };
+        current = fold_step(&current, sibling_hash, &direction);
+    }
+    return current == root_hash;
+}
+
+pub fn verify_proof_u64(
+    value: u64,
+    endian: Endian,
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    return verify_proof(leaf_hash_u64(value, endian), proof, root_hash);
+}
+
+pub fn verify_reveal(
+    value: &[u8],
+    nonce: &[u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    return verify_proof(commitment(value, nonce), proof, root_hash);
+}
+
+#[cfg(feature = "std")]
+pub fn verify_proof_hex_steps(
+    leaf_hex: &str,
+    steps: &[(String, Direction)],
+    root_hex: &str,
+) -> Result<bool, HexError> {
+    let leaf_hash = hex::decode32(leaf_hex)?;
+    let root_hash = hex::decode32(root_hex)?;
+    let mut proof = Vec::with_capacity(steps.len());
+    for (sibling_hex, direction) in steps {
+        proof.push((hex::decode32(sibling_hex)?, *direction));
+    }
+    return Ok(verify_proof(leaf_hash, &proof, root_hash));
+}
+
+pub fn verify_kv_proof(
+    key: &[u8],
+    value: &[u8],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    return verify_proof(kv_leaf_hash(key, value), proof, root_hash);
+}
+
+pub fn verify_value_strict(
+    value: &[u8],
+    claimed_leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    if sha256(value) != claimed_leaf_hash {
+        return false;
+    }
+    return verify_proof(claimed_leaf_hash, proof, root_hash);
+}
+
+pub fn verify_log_entry(
+    seq: u64,
+    entry: &[u8],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    return verify_proof(log_entry_hash(seq, entry), proof, root_hash);
+}
+
+pub fn verify_proof_versioned(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    inner_root: [u8; 32],
+    version: u32,
+    tagged_root: [u8; 32],
+) -> bool {
+    return version_tagged_root(version, &inner_root) == tagged_root
+        && verify_proof(leaf_hash, proof, inner_root);
+}
+
+pub fn verify_proof_optional_siblings(
+    leaf_hash: [u8; 32],
+    steps: &[(Option<[u8; 32]>, Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    let mut current = leaf_hash;
+    for (sibling_hash, direction) in steps {
+        let sibling_hash = sibling_hash.unwrap_or(current);
+        current = fold_step(&current, &sibling_hash, direction);
+    }
+    return current == root_hash;
+}
+
+pub fn verify_proof_counting(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> (bool, usize) {
+    let mut current = leaf_hash;
+    let mut hash_count = 0;
+    for (sibling_hash, direction) in proof {
+        current = fold_step(&current, sibling_hash, direction);
+        hash_count += 1;
+    }
+    return (current == root_hash, hash_count);
+}
+
+pub fn verify_proof_strict(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+) -> bool {
+    let mut current = leaf_hash;
+    for (sibling_hash, direction) in proof {
+        current = fold_step(&current, sibling_hash, direction);
+    }
+    return current == root_hash;
+}
+pub fn verify_proof_canonical(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+    leaf_count: usize,
+) -> bool {
+    let Some(index) = index_from_directions(proof) else {
+        return false;
+    };
+    if expected_proof_len(leaf_count, index) != Some(proof.len()) {
+        return false;
+    }
+    let mut current = leaf_hash;
+    for (level, (sibling_hash, direction)) in proof.iter().enumerate() {
+        let position = index >> level;
+        let padded = position + 1 == leaf_count.div_ceil(1 << level) && position.is_multiple_of(2);
+        if padded && *sibling_hash != current {
+            return false;
+        }
+        current = fold_step(&current, sibling_hash, direction);
+    }
+    return current == root_hash;
+}
+
+pub fn verify_proof_prefix_steps(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    k: usize,
+) -> Option<[u8; 32]> {
+    let mut current = leaf_hash;
+    for (sibling_hash, direction) in proof.get(..k)? {
+        current = fold_step(&current, sibling_hash, direction);
+    }
+    return Some(current);
+}
+
+pub fn proof_fold_iter<'a>(
+    leaf_hash: [u8; 32],
+    proof: &'a [([u8; 32], Direction)],
+) -> impl Iterator<Item = [u8; 32]> + 'a {
+    return proof
+        .iter()
+        .scan(leaf_hash, |current, (sibling_hash, direction)| {
+            *current = fold_step(current, sibling_hash, direction);
+            return Some(*current);
+        });
+}
+
+pub fn expected_proof_len(leaf_count: usize, index: usize) -> Option<usize> {
+    if index >= leaf_count {
+        return None;
+    }
+    return Some((usize::BITS - (leaf_count - 1).leading_zeros()) as usize);
+}
+
+pub fn plausible_tree_sizes(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+    max_size: usize,
+) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    if !verify_proof(leaf_hash, proof, root_hash) {
+        return sizes;
+    }
+    let Some(index) = index_from_directions(proof) else {
+        return sizes;
+    };
+    let mut current = leaf_hash;
+    let mut folded = Vec::with_capacity(proof.len());
+    for (sibling_hash, direction) in proof {
+        folded.push(current);
+        current = fold_step(&current, sibling_hash, direction);
+    }
+    let (min_leaves, max_leaves) = proof_size_bounds(proof.len());
+    let min_size = min_leaves.max(index.saturating_add(1));
+    for size in min_size..=max_leaves.min(max_size) {
+        if expected_proof_len(size, index) != Some(proof.len()) {
+            continue;
+        }
+        let padding_fits =
+            proof
+                .iter()
+                .zip(&folded)
+                .enumerate()
+                .all(|(level, ((sibling_hash, _), current))| {
+                    let position = index >> level;
+                    return !position.is_multiple_of(2)
+                        || position + 1 < size.div_ceil(1 << level)
+                        || sibling_hash == current;
+                });
+        if padding_fits {
+            sizes.push(size);
+        }
+    }
+    return sizes;
+}
+
+pub fn verify_proof_minimal(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+    leaf_count: usize,
+) -> bool {
+    let index = match index_from_directions(proof) {
+        Some(index) => index,
+        None => return false,
+    };
+    if expected_proof_len(leaf_count, index) != Some(proof.len()) {
+        return false;
+    }
+    return verify_proof(leaf_hash, proof, root_hash);
+}
+
+pub fn verify_proof_autodetect(
+    leaf_hash: [u8; 32],
+    proof: &[([u8; 32], Direction)],
+    root_hash: [u8; 32],
+    leaf_count: usize,
+) -> Option<PaddingStrategy> {
+    if !verify_proof(leaf_hash, proof, root_hash) {
+        return None;
+    }
+    return [PaddingStrategy::Duplicate, PaddingStrategy::Promote]
+        .into_iter()
+        .find(|padding| index_for_padding(proof, leaf_count, *padding).is_some());
+}
+
+pub fn proof_to_index_form(proof: &[([u8; 32], Direction)]) -> Option<(usize, Vec<[u8; 32]>)> {
+    let index = index_from_directions(proof)?;
+    let siblings = proof.iter().map(|(sibling, _)| *sibling).collect();
+    return Some((index, siblings));
+}
+
+pub fn index_form_to_proof(
+    index: usize,
+    siblings: &[[u8; 32]],
+    tree_size: usize,
+) -> Option<Vec<([u8; 32], Direction)>> {
+    if expected_proof_len(tree_size, index)? != siblings.len() {
+        return None;
+    }
+    let proof = siblings
+        .iter()
+        .enumerate()
+        .map(|(level, sibling)| {
+            let direction = if (index >> level) & 1 == 1 {
+                Direction::Left
+            } else {
+                Direction::Right
+            };
+            (*sibling, direction)
+        })
+        .collect();
+    return Some(proof);
+}
+
+pub fn proof_storage_estimate(leaf_count: usize) -> usize {
+    let proof_len = match expected_proof_len(leaf_count, 0) {
+        Some(proof_len) => proof_len,
+        None => return 0,
+    };
+    return leaf_count.saturating_mul(proof_len).saturating_mul(33);
+}
+
+pub fn max_leaves_for_depth(depth: usize) -> usize {
+    if depth >= usize::BITS as usize {
+        return usize::MAX;
+    }
+    return 1 << depth;
+}
+
+pub fn proof_size_bounds(proof_len: usize) -> (usize, usize) {
+    if proof_len == 0 {
+        return (1, 1);
+    }
+    let power = |exp: usize| 1usize.checked_shl(exp as u32).unwrap_or(usize::MAX);
+    let min_leaves = power(proof_len - 1).saturating_add(1);
+    let max_leaves = power(proof_len);
+    return (min_leaves, max_leaves);
+}
+
+pub fn common_prefix_len(a: &MerkleTree, b: &MerkleTree) -> usize {
+    return a
+        .leaves
+        .iter()
+        .zip(&b.leaves)
+        .take_while(|(left, right)| left == right)
+        .count();
+}
+
+pub fn sibling_digest(proof: &[([u8; 32], Direction)]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(proof.len() * 33);
+    for (sibling, direction) in proof {
+        data.extend_from_slice(sibling);
+        data.push(match direction {
+            Direction::Left => 0,
+            Direction::Right => 1,
+        });
+    }
+    return sha256(&data);
+}
+
+fn index_for_padding(
+    proof: &[([u8; 32], Direction)],
+    leaf_count: usize,
+    padding: PaddingStrategy,
+) -> Option<usize> {
+    if leaf_count == 0 {
+        return None;
+    }
+    let mut level_lens = Vec::new();
+    let mut level_len = leaf_count;
+    while level_len > 1 {
+        level_lens.push(level_len);
+        level_len = level_len.div_ceil(2);
+    }
+    let mut steps = proof.iter().rev();
+    let mut position = 0usize;
+    for level_len in level_lens.into_iter().rev() {
+        if padding == PaddingStrategy::Promote && 2 * position == level_len - 1 {
+            position *= 2;
+            continue;
+        }
+        let (_, direction) = steps.next()?;
+        position = 2 * position + usize::from(*direction == Direction::Left);
+        if position >= level_len {
+            return None;
+        }
+    }
+    if steps.next().is_some() {
+        return None;
+    }
+    return Some(position);
+}
+
+pub(crate) fn index_from_directions(proof: &[([u8; 32], Direction)]) -> Option<usize> {
+    let mut index = 0usize;
+    for (level, (_, direction)) in proof.iter().enumerate() {
+        if *direction == Direction::Left {
+            index |= 1usize.checked_shl(level as u32)?;
+        }
+    }
+    return Some(index);
+}
 
-        let mut data = Vec::with_capacity(left.hash.len() + right.hash.len());
-        data.extend_from_slice(&left.hash);
-        data.extend_from_slice(&right.hash);
+pub(crate) fn fold_step(
+    current: &[u8; 32],
+    sibling_hash: &[u8; 32],
+    direction: &Direction,
+) -> [u8; 32] {
+    return fold_step_with(&Sha256Hasher, current, sibling_hash, direction);
+}
+
+pub(crate) fn fold_step_with<H: Hasher<N>, const N: usize>(
+    hasher: &H,
+    current: &[u8; N],
+    sibling_hash: &[u8; N],
+    direction: &Direction,
+) -> [u8; N] {
+    return match direction {
+        Direction::Left => hash_pair_with(hasher, sibling_hash, current),
+        Direction::Right => hash_pair_with(hasher, current, sibling_hash),
+    };
+}
+
+pub(crate) fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    return hash_pair_with(&Sha256Hasher, left, right);
+}
+
+pub(crate) fn hash_pair_with<H: Hasher<N>, const N: usize>(
+    hasher: &H,
+    left: &[u8; N],
+    right: &[u8; N],
+) -> [u8; N] {
+    return hasher.hash_nodes(left, right);
+}
+
+fn build_leaves_array<T: AsRef<[u8]>, H: Hasher<N>, const N: usize>(
+    values: &[T],
+    hasher: &H,
+) -> Vec<MerkleNode<N>> {
+    return values
+        .iter()
+        .map(|value| {
+            let hash = hasher.hash_leaf(value.as_ref());
+            MerkleNode {
+                hash,
+                left: None,
+                right: None,
+            }
+        })
+        .collect();
+}
+
+fn build_merkle_tree_recursively<H: Hasher<N>, const N: usize>(
+    nodes: &[MerkleNode<N>],
+    hasher: &H,
+    padding: PaddingStrategy,
+) -> MerkleNode<N> {
+    if nodes.len() == 1 {
+        return nodes[0].clone();
+    }
+    let mut parents = Vec::new();
+    let mut i: usize = 0;
+
+    while i < nodes.len() {
+        if i + 1 == nodes.len() && padding == PaddingStrategy::Promote {
+            parents.push(nodes[i].clone());
+            break;
+        }
+        let left = nodes[i].clone();
+        let right = if i + 1 < nodes.len() {
+            nodes[i + 1].clone()
+        } else {
+            nodes[i].clone()
+        };
 
-        let hash = sha256(&data);
+        let hash = hash_pair_with(hasher, &left.hash, &right.hash);
         parents.push(MerkleNode {
             hash,
             left: Some(Box::new(left)),
@@ -125,181 +1373,2383 @@ fn build_merkle_tree_recursively(nodes: &[MerkleNode]) -> MerkleNode {
         });
         i += 2;
     }
-    return build_merkle_tree_recursively(&parents);
+    return build_merkle_tree_recursively(&parents, hasher, padding);
+}
+
+#[cfg(feature = "rayon")]
+fn build_merkle_tree_par<H: Hasher<N> + Sync, const N: usize>(
+    mut nodes: Vec<MerkleNode<N>>,
+    hasher: &H,
+) -> MerkleNode<N> {
+    while nodes.len() > 1 {
+        nodes = nodes
+            .into_par_iter()
+            .chunks(2)
+            .map(|mut pair| {
+                let right = pair.pop().expect("chunks are never empty");
+                let left = pair.pop().unwrap_or_else(|| right.clone());
+                MerkleNode {
+                    hash: hash_pair_with(hasher, &left.hash, &right.hash),
+                    left: Some(Box::new(left)),
+                    right: Some(Box::new(right)),
+                }
+            })
+            .collect();
+    }
+    return nodes.pop().expect("non-empty level");
+}
+
+fn update_path<H: Hasher<N>, const N: usize>(
+    node: &mut MerkleNode<N>,
+    level: usize,
+    index: usize,
+    leaf_count: usize,
+    leaf_hash: [u8; N],
+    hasher: &H,
+) {
+    if level == 0 {
+        node.hash = leaf_hash;
+        return;
+    }
+    let (Some(left), Some(right)) = (node.left.as_deref_mut(), node.right.as_deref_mut()) else {
+        unreachable!("internal node without children");
+    };
+    let child = index >> (level - 1);
+    if child.is_multiple_of(2) {
+        update_path(left, level - 1, index, leaf_count, leaf_hash, hasher);
+        let child_level_len = leaf_count.div_ceil(1 << (level - 1));
+        if child + 1 >= child_level_len {
+            update_path(right, level - 1, index, leaf_count, leaf_hash, hasher);
+        }
+    } else {
+        update_path(right, level - 1, index, leaf_count, leaf_hash, hasher);
+    }
+    node.hash = hash_pair_with(hasher, &left.hash, &right.hash);
+}
+
+pub(crate) fn build_levels<H: Hasher<N>, const N: usize>(
+    leaves: &[[u8; N]],
+    hasher: &H,
+    padding: PaddingStrategy,
+) -> Vec<Vec<[u8; N]>> {
+    let mut levels = Vec::new();
+    if leaves.is_empty() {
+        return levels;
+    }
+    let mut current = leaves.to_vec();
+    while current.len() > 1 {
+        let next = current
+            .chunks(2)
+            .map(|pair| parent_hash(pair, hasher, padding))
+            .collect();
+        levels.push(current);
+        current = next;
+    }
+    levels.push(current);
+    return levels;
+}
+
+fn build_fixed_levels<H: Hasher<N>, const N: usize>(
+    leaves: &[[u8; N]],
+    hasher: &H,
+    depth: u32,
+) -> Vec<Vec<[u8; N]>> {
+    let mut zero = [0u8; N];
+    let mut levels = Vec::with_capacity(depth as usize + 1);
+    let mut current = leaves.to_vec();
+    for _ in 0..depth {
+        if current.len() % 2 == 1 {
+            current.push(zero);
+        }
+        let next = current
+            .chunks(2)
+            .map(|pair| hash_pair_with(hasher, &pair[0], &pair[1]))
+            .collect();
+        levels.push(current);
+        current = next;
+        zero = hash_pair_with(hasher, &zero, &zero);
+    }
+    if current.is_empty() {
+        current.push(zero);
+    }
+    levels.push(current);
+    return levels;
+}
+
+pub(crate) fn parent_hash<H: Hasher<N>, const N: usize>(
+    pair: &[[u8; N]],
+    hasher: &H,
+    padding: PaddingStrategy,
+) -> [u8; N] {
+    return match (pair.get(1), padding) {
+        (Some(right), _) => hash_pair_with(hasher, &pair[0], right),
+        (None, PaddingStrategy::Duplicate) => hash_pair_with(hasher, &pair[0], &pair[0]),
+        (None, PaddingStrategy::Promote) => pair[0],
+    };
+}
+
+fn refresh_path<H: Hasher<N>, const N: usize>(
+    layers: &mut Vec<Vec<[u8; N]>>,
+    index: usize,
+    hasher: &H,
+    padding: PaddingStrategy,
+) {
+    let mut position = index;
+    let mut level = 0;
+    while layers[level].len() > 1 {
+        let start = position & !1;
+        let end = (start + 2).min(layers[level].len());
+        let parent = parent_hash(&layers[level][start..end], hasher, padding);
+        position /= 2;
+        if level + 1 == layers.len() {
+            layers.push(Vec::new());
+        }
+        let above = &mut layers[level + 1];
+        if position < above.len() {
+            above[position] = parent;
+        } else {
+            above.push(parent);
+        }
+        level += 1;
+    }
+}
+
+fn proof_from_levels<const N: usize>(
+    levels: &[Vec<[u8; N]>],
+    index: usize,
+    padding: PaddingStrategy,
+) -> Option<Vec<([u8; N], Direction)>> {
+    if index >= levels.first()?.len() {
+        return None;
+    }
+    let mut proof = Vec::with_capacity(levels.len() - 1);
+    let mut position = index;
+    for level in &levels[..levels.len() - 1] {
+        if position.is_multiple_of(2) {
+            match (level.get(position + 1), padding) {
+                (Some(sibling), _) => proof.push((*sibling, Direction::Right)),
+                (None, PaddingStrategy::Duplicate) => {
+                    proof.push((level[position], Direction::Right));
+                }
+                (None, PaddingStrategy::Promote) => {}
+            }
+        } else {
+            proof.push((level[position - 1], Direction::Left));
+        }
+        position /= 2;
+    }
+    return Some(proof);
+}
+
+#[cfg(feature = "std")]
+fn draw_children<const N: usize>(
+    levels: &[Vec<[u8; N]>],
+    level: usize,
+    position: usize,
+    padding: PaddingStrategy,
+    indent: &str,
+    art: &mut String,
+) {
+    if level == 0 {
+        return;
+    }
+    let below = &levels[level - 1];
+    let left = 2 * position;
+    if left >= below.len() {
+        return;
+    }
+    let mut children = vec![left];
+    if left + 1 < below.len() {
+        children.push(left + 1);
+    } else if padding == PaddingStrategy::Duplicate {
+        children.push(left);
+    }
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let connector = if last { "`-- " } else { "+-- " };
+        let label = if level == 1 {
+            format!("leaf {} {}", child, short_hex(&below[*child]))
+        } else {
+            short_hex(&below[*child])
+        };
+        art.push_str(&format!("{}{}{}\n", indent, connector, label));
+        let child_indent = format!("{}{}", indent, if last { "    " } else { "|   " });
+        draw_children(levels, level - 1, *child, padding, &child_indent, art);
+    }
+}
+
+#[cfg(feature = "std")]
+fn node_from_levels<const N: usize>(
+    levels: &[Vec<[u8; N]>],
+    level: usize,
+    position: usize,
+    padding: PaddingStrategy,
+) -> MerkleNode<N> {
+    let hash = levels[level][position];
+    let left = 2 * position;
+    if level == 0 || left >= levels[level - 1].len() {
+        return MerkleNode {
+            hash,
+            left: None,
+            right: None,
+        };
+    }
+    let left_node = node_from_levels(levels, level - 1, left, padding);
+    let right_node = if left + 1 < levels[level - 1].len() {
+        node_from_levels(levels, level - 1, left + 1, padding)
+    } else if padding == PaddingStrategy::Promote {
+        return left_node;
+    } else {
+        left_node.clone()
+    };
+    return MerkleNode {
+        hash,
+        left: Some(Box::new(left_node)),
+        right: Some(Box::new(right_node)),
+    };
+}
+
+#[cfg(feature = "std")]
+fn write_dot_node<const N: usize>(
+    node: &MerkleNode<N>,
+    next_id: &mut usize,
+    dot: &mut String,
+) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    dot.push_str(&format!(
+        "  n{} [label=\"{}\"];\n",
+        id,
+        short_hex(&node.hash)
+    ));
+    for (child, label) in [(&node.left, "L"), (&node.right, "R")] {
+        if let Some(child) = child {
+            let child_id = write_dot_node(child, next_id, dot);
+            dot.push_str(&format!(
+                "  n{} -> n{} [label=\"{}\"];\n",
+                id, child_id, label
+            ));
+        }
+    }
+    return id;
+}
+
+#[cfg(feature = "std")]
+fn short_hex(hash: &[u8]) -> String {
+    return hex::encode(&hash[..hash.len().min(4)]);
 }
 
-fn dfs_generate_proof(
-    node: &MerkleNode,
-    target: &[u8; 32],
-    proof: &mut Vec<([u8; 32], Direction)>,
+fn dfs_generate_proof<const N: usize>(
+    node: &MerkleNode<N>,
+    target: &[u8; N],
+    proof: &mut Vec<([u8; N], Direction)>,
 ) -> bool {
     if node.left.is_none() && node.right.is_none() {
         return &node.hash == target;
     }
-    if let Some(left) = &node.left {
-        if dfs_generate_proof(left, target, proof) {
-            if let Some(right) = &node.right {
-                proof.push((right.hash, Direction::Right));
+    if let Some(left) = &node.left
+        && dfs_generate_proof(left, target, proof)
+    {
+        if let Some(right) = &node.right {
+            proof.push((right.hash, Direction::Right));
+        }
+        return true;
+    }
+    if let Some(right) = &node.right
+        && dfs_generate_proof(right, target, proof)
+    {
+        if let Some(left) = &node.left {
+            proof.push((left.hash, Direction::Left));
+        }
+        return true;
+    }
+    return false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    mod tree_construction {
+        use super::*;
+        #[test]
+        fn merkle_tree_is_deterministic() {
+            let data = vec!["a", "b", "c", "d"];
+            let t1 = MerkleTree::from_bytes(&data);
+            let t2 = MerkleTree::from_bytes(&data);
+            assert_eq!(t1.get_root(), t2.get_root());
+        }
+        #[test]
+        fn empty_input_has_no_root() {
+            let data: Vec<&[u8]> = vec![];
+            let tree = MerkleTree::from_bytes(&data);
+            assert!(tree.root.is_none());
+        }
+        #[test]
+        fn single_element_tree() {
+            let data: Vec<&[u8]> = vec![&[42u8]];
+            let tree = MerkleTree::from_bytes(&data);
+            let expected = sha256(&[42u8]);
+            assert_eq!(tree.get_root(), Some(&expected));
+        }
+        #[test]
+        fn from_single_root_is_leaf_hash() {
+            let tree = MerkleTree::from_single("x".as_bytes());
+            let leaf_hash = sha256("x".as_bytes());
+            assert_eq!(tree.get_root(), Some(&leaf_hash));
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            assert!(proof.is_empty());
+            assert!(verify_proof(leaf_hash, &proof, leaf_hash));
+        }
+        #[test]
+        fn multiple_elements_tree_has_root() {
+            let data: Vec<&[u8]> = vec![&[1], &[2], &[3], &[4]];
+            let tree = MerkleTree::from_bytes(&data);
+            assert!(tree.root.is_some());
+        }
+        #[test]
+        fn odd_number_of_elements_is_supported() {
+            let data: Vec<&[u8]> = vec![&[1], &[2], &[3]];
+            let tree = MerkleTree::from_bytes(&data);
+            assert!(tree.root.is_some());
+        }
+    }
+    mod fixed_depth {
+        use super::*;
+        #[test]
+        fn input_fitting_exactly_is_unchanged() {
+            let values = ["a", "b", "c", "d"];
+            let tree = MerkleTree::from_bytes_fixed_depth(&values, 2).unwrap();
+            assert_eq!(tree.get_root(), MerkleTree::from_bytes(&values).get_root());
+        }
+        #[test]
+        fn short_input_is_padded_with_zero_leaves() {
+            let tree = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c"], 3).unwrap();
+            let mut leaves: Vec<[u8; 32]> = ["a", "b", "c"]
+                .iter()
+                .map(|v| sha256(v.as_bytes()))
+                .collect();
+            leaves.resize(8, [0u8; 32]);
+            let expected = MerkleTree::from_leaf_hashes_vec(leaves);
+            assert_eq!(tree.get_root(), expected.get_root());
+            assert_eq!(tree.generate_proof_by_index(2).unwrap().len(), 3);
+        }
+        #[test]
+        fn deep_tree_only_hashes_the_filled_branch() {
+            let zeros = crate::smt::default_hashes(40);
+            let leaves = ["a", "b", "c"].map(|v| sha256(v.as_bytes()));
+            let mut expected = hash_pair(
+                &hash_pair(&leaves[0], &leaves[1]),
+                &hash_pair(&leaves[2], &zeros[0]),
+            );
+            for zero in &zeros[2..40] {
+                expected = hash_pair(&expected, zero);
+            }
+            let tree = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c"], 40).unwrap();
+            assert_eq!(tree.get_root(), Some(&expected));
+            assert_eq!(tree.len(), 3);
+            assert_eq!(tree.depth(), 41);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert_eq!(proof.len(), 40);
+                assert!(verify_proof(*leaf, &proof, expected));
+            }
+            assert_eq!(tree.generate_proof_by_index(3), None);
+            let empty = MerkleTree::from_bytes_fixed_depth::<&str>(&[], 63).unwrap();
+            assert_eq!(empty.get_root(), Some(&crate::smt::default_hashes(63)[63]));
+        }
+        #[test]
+        fn push_and_update_keep_the_fixed_depth() {
+            let mut tree = MerkleTree::from_bytes_fixed_depth(&["a", "b"], 3).unwrap();
+            tree.push(b"c");
+            tree.update(0, b"z").unwrap();
+            let expected = MerkleTree::from_bytes_fixed_depth(&["z", "b", "c"], 3).unwrap();
+            assert_eq!(tree, expected);
+            assert_eq!(tree.generate_proof_by_index(2).unwrap().len(), 3);
+        }
+        #[test]
+        fn pushing_past_the_depth_is_rejected() {
+            let mut full = MerkleTree::from_bytes_fixed_depth(&["a", "b"], 1).unwrap();
+            let expected = full.clone();
+            assert_eq!(
+                full.try_push(b"c"),
+                Err(BuildError::TooDeep {
+                    leaf_count: 3,
+                    depth: 1
+                })
+            );
+            full.push(b"c");
+            assert_eq!(full, expected);
+            let mut tree = MerkleTree::from_bytes_fixed_depth(&["a"], 2).unwrap();
+            assert_eq!(
+                tree.try_extend(&["b", "c", "d", "e"]),
+                Err(BuildError::TooDeep {
+                    leaf_count: 5,
+                    depth: 2
+                })
+            );
+            tree.extend(&["b", "c", "d", "e"]);
+            assert_eq!(tree.len(), 1);
+            assert_eq!(tree.try_extend(&["b", "c", "d"]), Ok(()));
+            assert_eq!(tree.generate_proof_by_index(3).unwrap().len(), 2);
+        }
+        #[test]
+        fn input_exceeding_depth_is_rejected() {
+            let result = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c"], 1);
+            assert_eq!(
+                result.err(),
+                Some(BuildError::TooDeep {
+                    leaf_count: 3,
+                    depth: 1
+                })
+            );
+            let result = MerkleTree::from_bytes_fixed_depth(&["a"], usize::BITS);
+            assert_eq!(
+                result.err(),
+                Some(BuildError::DepthOverflow { depth: usize::BITS })
+            );
+        }
+    }
+    mod empty_roots {
+        use super::*;
+        #[test]
+        fn empty_root_is_hash_of_empty_input() {
+            assert_eq!(MerkleTree::empty_root(), sha256(&[]));
+        }
+        #[test]
+        fn empty_tree_root_depends_on_mode() {
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(empty.get_root(), None);
+            let canonical = empty.with_empty_root_mode(EmptyRootMode::Canonical);
+            assert_eq!(canonical.get_root(), Some(&MerkleTree::empty_root()));
+        }
+        #[test]
+        fn mode_does_not_change_non_empty_roots() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let expected = *tree.get_root().unwrap();
+            let mut canonical = tree.with_empty_root_mode(EmptyRootMode::Canonical);
+            assert_eq!(canonical.get_root(), Some(&expected));
+            canonical.clear();
+            assert_eq!(canonical.get_root(), Some(&MerkleTree::empty_root()));
+        }
+    }
+    mod iterator_leaves {
+        use super::*;
+        #[test]
+        fn streamed_hashes_match_slice_of_hashes() {
+            let hashes: Vec<[u8; 32]> = (0..4u8).map(|i| sha256(&[i])).collect();
+            let tree = MerkleTree::from_iter_leaf_hashes((0..4u8).map(|i| sha256(&[i])));
+            let expected = MerkleTree::from_leaf_hashes_vec(hashes);
+            assert_eq!(tree.get_root(), expected.get_root());
+            let values: Vec<[u8; 1]> = (0..4u8).map(|i| [i]).collect();
+            assert_eq!(tree.get_root(), MerkleTree::from_bytes(&values).get_root());
+        }
+        #[test]
+        fn empty_iterator_has_no_root() {
+            let tree = MerkleTree::from_iter_leaf_hashes(std::iter::empty());
+            assert_eq!(tree.get_root(), None);
+        }
+    }
+    mod prehashed_leaves {
+        use super::*;
+        #[test]
+        fn hashes_are_not_hashed_again() {
+            let tree = MerkleTree::from_hashes(&[sha256(b"a"), sha256(b"b")]);
+            let expected = MerkleTree::from_bytes(&["a", "b"]);
+            assert_eq!(tree.get_root(), expected.get_root());
+            assert_eq!(tree, expected);
+        }
+        #[test]
+        fn leaves_can_be_roots_of_other_trees() {
+            let left = MerkleTree::from_bytes(&["a", "b"]);
+            let right = MerkleTree::from_bytes(&["c", "d"]);
+            let tree =
+                MerkleTree::from_hashes(&[*left.get_root().unwrap(), *right.get_root().unwrap()]);
+            let whole = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            assert_eq!(tree.get_root(), whole.get_root());
+            assert_eq!(MerkleTree::from_hashes(&[]).get_root(), None);
+        }
+    }
+    mod hybrid_hashers {
+        use super::*;
+        #[derive(Clone, Copy)]
+        struct PrefixedSha256;
+        impl Hasher for PrefixedSha256 {
+            fn digest(&self, data: &[u8]) -> [u8; 32] {
+                let mut prefixed = vec![0x01];
+                prefixed.extend_from_slice(data);
+                return sha256(&prefixed);
+            }
+        }
+        #[test]
+        fn hybrid_root_differs_from_uniform_sha256() {
+            let values = ["a", "b", "c"];
+            let hybrid = MerkleTree::from_bytes_with(
+                &values,
+                HybridHasher::new(Sha256Hasher, PrefixedSha256),
+            );
+            let uniform = MerkleTree::from_bytes(&values);
+            assert_ne!(hybrid.get_root(), uniform.get_root());
+            assert_eq!(hybrid.leaves, uniform.leaves);
+        }
+        #[test]
+        fn proofs_verify_under_matching_hybrid_config() {
+            let config = HybridHasher::new(Sha256Hasher, PrefixedSha256);
+            let tree = MerkleTree::from_bytes_with(&["a", "b", "c"], config);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            let leaf = sha256("b".as_bytes());
+            assert!(verify_proof_hybrid(&config, leaf, &proof, root));
+            assert!(!verify_proof(leaf, &proof, root));
+            let swapped = HybridHasher::new(PrefixedSha256, Sha256Hasher);
+            assert!(!verify_proof_hybrid(&swapped, leaf, &proof, root));
+        }
+    }
+    mod size_and_depth {
+        use super::*;
+        #[test]
+        fn empty_tree() {
+            let tree = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(tree.len(), 0);
+            assert!(tree.is_empty());
+            assert_eq!(tree.depth(), 0);
+        }
+        #[test]
+        fn single_leaf_tree() {
+            let tree = MerkleTree::from_single("a".as_bytes());
+            assert_eq!(tree.len(), 1);
+            assert!(!tree.is_empty());
+            assert_eq!(tree.depth(), 1);
+        }
+        #[test]
+        fn five_leaf_padded_tree() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            assert_eq!(tree.len(), 5);
+            assert_eq!(tree.depth(), 4);
+            assert_eq!(tree.depth(), tree.stats().depth);
+            let proof = tree.generate_proof_by_index(4).unwrap();
+            assert_eq!(proof.len(), tree.depth() - 1);
+        }
+    }
+    mod standard_traits {
+        use super::*;
+        #[test]
+        fn clone_is_equal_until_push() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let mut copy = tree.clone();
+            assert_eq!(copy, tree);
+            copy.push("d".as_bytes());
+            assert_ne!(copy, tree);
+            assert_eq!(copy, MerkleTree::from_bytes(&["a", "b", "c", "d"]));
+        }
+        #[test]
+        #[cfg(feature = "std")]
+        fn debug_shows_hex_root_and_leaf_count() {
+            let tree = MerkleTree::from_single(&[]);
+            let expected = format!(
+                "MerkleTree {{ root: Some({}), leaf_count: 1 }}",
+                hex::encode(&sha256(&[]))
+            );
+            assert_eq!(format!("{:?}", tree), expected);
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(
+                format!("{:?}", empty),
+                "MerkleTree { root: None, leaf_count: 0 }"
+            );
+            assert_eq!(format!("{:?}", Direction::Left), "Left");
+        }
+    }
+    mod pluggable_hashers {
+        use super::*;
+        struct DoubleSha256;
+        impl Hasher for DoubleSha256 {
+            fn digest(&self, data: &[u8]) -> [u8; 32] {
+                return sha256(&sha256(data));
+            }
+        }
+        #[test]
+        fn default_hasher_matches_from_bytes() {
+            let values = ["a", "b", "c"];
+            let tree = MerkleTree::from_bytes_with(&values, Sha256Hasher);
+            assert_eq!(tree.get_root(), MerkleTree::from_bytes(&values).get_root());
+        }
+        #[test]
+        fn different_hashers_produce_different_roots() {
+            let values = ["a", "b", "c"];
+            let sha = MerkleTree::from_bytes(&values);
+            let double = MerkleTree::from_bytes_with(&values, DoubleSha256);
+            assert_ne!(sha.get_root(), double.get_root());
+        }
+        #[test]
+        fn proofs_verify_with_the_same_hasher() {
+            let mut tree = MerkleTree::from_bytes_with(&["a", "b", "c"], DoubleSha256);
+            tree.push("d".as_bytes());
+            let root = *tree.get_root().unwrap();
+            let leaf = DoubleSha256.digest("d".as_bytes());
+            let proof = tree.generate_proof_by_index(3).unwrap();
+            assert_eq!(tree.generate_proof(&leaf), Some(proof.clone()));
+            assert!(verify_proof_with(&DoubleSha256, leaf, &proof, root));
+            assert!(!verify_proof(leaf, &proof, root));
+        }
+        #[test]
+        fn canonical_empty_root_uses_the_hasher() {
+            let empty = MerkleTree::from_bytes_with::<&str>(&[], DoubleSha256)
+                .with_empty_root_mode(EmptyRootMode::Canonical);
+            assert_eq!(empty.get_root(), Some(&DoubleSha256.digest(&[])));
+        }
+        #[cfg(feature = "blake3")]
+        #[test]
+        fn keyed_and_unkeyed_blake3_trees_differ() {
+            use crate::hash::Blake3Hasher;
+            let values = ["a", "b", "c"];
+            let unkeyed = MerkleTree::from_bytes_with(&values, Blake3Hasher::new());
+            let keyed = MerkleTree::from_bytes_with(&values, Blake3Hasher::keyed([7; 32]));
+            let other_key = MerkleTree::from_bytes_with(&values, Blake3Hasher::keyed([8; 32]));
+            assert_ne!(unkeyed.get_root(), keyed.get_root());
+            assert_ne!(keyed.get_root(), other_key.get_root());
+            assert_ne!(
+                unkeyed.get_root(),
+                MerkleTree::from_bytes(&values).get_root()
+            );
+            let root = *keyed.get_root().unwrap();
+            let leaf = Blake3Hasher::keyed([7; 32]).digest("b".as_bytes());
+            let proof = keyed.generate_proof_by_index(1).unwrap();
+            assert!(verify_proof_with(
+                &Blake3Hasher::keyed([7; 32]),
+                leaf,
+                &proof,
+                root
+            ));
+            assert!(!verify_proof_with(&Blake3Hasher::new(), leaf, &proof, root));
+        }
+        #[cfg(feature = "blake3")]
+        #[test]
+        fn blake3_matches_the_reference_digest() {
+            use crate::hash::Blake3Hasher;
+            let tree = MerkleTree::from_bytes_with(&["abc"], Blake3Hasher::new());
+            let expected: [u8; 32] = [
+                0x64, 0x37, 0xb3, 0xac, 0x38, 0x46, 0x51, 0x33, 0xff, 0xb6, 0x3b, 0x75, 0x27, 0x3a,
+                0x8d, 0xb5, 0x48, 0xc5, 0x58, 0x46, 0x5d, 0x79, 0xdb, 0x03, 0xfd, 0x35, 0x9c, 0x6c,
+                0xd5, 0xbd, 0x9d, 0x85,
+            ];
+            assert_eq!(tree.get_root(), Some(&expected));
+        }
+    }
+    mod digest_sizes {
+        use super::*;
+        struct TruncatedSha256;
+        impl Hasher<16> for TruncatedSha256 {
+            fn digest(&self, data: &[u8]) -> [u8; 16] {
+                return sha256(data)[..16].try_into().unwrap();
+            }
+        }
+        #[test]
+        fn truncated_hasher_builds_16_byte_tree() {
+            let values = ["a", "b", "c", "d", "e"];
+            let tree = MerkleTree::from_bytes_with(&values, TruncatedSha256);
+            let root: [u8; 16] = *tree.get_root().unwrap();
+            let left = TruncatedSha256.hash_nodes(&tree.leaves()[0], &tree.leaves()[1]);
+            assert_eq!(tree.level_nodes(1).unwrap()[0], left);
+            for (index, value) in values.iter().enumerate() {
+                let leaf = TruncatedSha256.digest(value.as_bytes());
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert_eq!(proof.len(), 3);
+                assert!(verify_proof_with(&TruncatedSha256, leaf, &proof, root));
+                assert!(!verify_proof_with(
+                    &TruncatedSha256,
+                    [0u8; 16],
+                    &proof,
+                    root
+                ));
+                assert_eq!(tree.generate_proof(&leaf), Some(proof));
+            }
+        }
+        #[test]
+        fn truncated_tree_supports_push_and_update() {
+            let mut tree = MerkleTree::from_bytes_with(&["a", "b"], TruncatedSha256);
+            tree.push(b"c");
+            tree.update(0, b"z").unwrap();
+            let expected = MerkleTree::from_bytes_with(&["z", "b", "c"], TruncatedSha256);
+            assert_eq!(tree, expected);
+            #[cfg(feature = "std")]
+            assert!(tree.to_dot().contains("digraph"));
+        }
+        #[test]
+        fn hybrid_proofs_verify_with_16_byte_digests() {
+            struct TruncatedSha256Tail;
+            impl Hasher<16> for TruncatedSha256Tail {
+                fn digest(&self, data: &[u8]) -> [u8; 16] {
+                    return sha256(data)[16..].try_into().unwrap();
+                }
+            }
+            let hybrid = || HybridHasher::new(TruncatedSha256, TruncatedSha256Tail);
+            let tree = MerkleTree::from_bytes_with(&["a", "b", "c"], hybrid());
+            let root = *tree.get_root().unwrap();
+            let leaf = TruncatedSha256.digest(b"c");
+            let proof = tree.generate_proof_by_index(2).unwrap();
+            assert!(verify_proof_hybrid(&hybrid(), leaf, &proof, root));
+            let swapped = HybridHasher::new(TruncatedSha256Tail, TruncatedSha256);
+            assert!(!verify_proof_hybrid(&swapped, leaf, &proof, root));
+        }
+    }
+    mod sorted_leaf_hashes {
+        use super::*;
+        #[test]
+        fn matches_canonical_tree_for_pre_sorted_input() {
+            let values = ["d", "a", "c", "b", "e", "a"];
+            let canonical = MerkleTree::from_bytes_canonical(&values);
+            let mut hashes: Vec<[u8; 32]> = values.iter().map(|v| sha256(v.as_bytes())).collect();
+            hashes.sort();
+            hashes.dedup();
+            let tree = MerkleTree::from_sorted_leaf_hashes_unchecked(&hashes);
+            assert_eq!(tree, canonical);
+            assert_eq!(canonical.len(), 5);
+            assert_ne!(
+                canonical.get_root(),
+                MerkleTree::from_bytes(&values).get_root()
+            );
+            let proof = tree.generate_proof_by_index(3).unwrap();
+            assert!(verify_proof(hashes[3], &proof, *tree.get_root().unwrap()));
+        }
+        #[test]
+        fn canonical_tree_ignores_order_and_duplicates() {
+            let tree = MerkleTree::from_bytes_canonical(&["b", "a", "c"]);
+            assert_eq!(
+                tree,
+                MerkleTree::from_bytes_canonical(&["c", "b", "a", "b"])
+            );
+            assert!(tree.leaves().windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        #[test]
+        #[cfg(debug_assertions)]
+        #[should_panic(expected = "sorted and unique")]
+        fn unsorted_input_panics_in_debug_builds() {
+            let hashes = [sha256(b"a"), sha256(b"b")];
+            let (low, high) = if hashes[0] < hashes[1] {
+                (hashes[0], hashes[1])
+            } else {
+                (hashes[1], hashes[0])
+            };
+            MerkleTree::from_sorted_leaf_hashes_unchecked(&[high, low]);
+        }
+    }
+    mod proof_generation_and_verification {
+        use super::*;
+        #[test]
+        fn changing_inputs_changes_root() {
+            let base = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert_ne!(
+                base.get_root(),
+                MerkleTree::from_bytes(&["c", "b", "a"]).get_root()
+            );
+            assert_ne!(
+                base.get_root(),
+                MerkleTree::from_bytes(&["a", "b", "d"]).get_root()
+            );
+            assert_ne!(
+                base.get_root(),
+                MerkleTree::from_bytes(&["a", "b"]).get_root()
+            );
+        }
+        #[test]
+        fn generate_and_verify_proof_for_each_leaf() {
+            let data = vec!["a", "b", "c", "d"];
+            let tree = MerkleTree::from_bytes(&data);
+            let root = *tree.get_root().unwrap();
+            for value in &data {
+                let leaf_hash = sha256(value.as_bytes());
+                let proof = tree.generate_proof(&leaf_hash).unwrap();
+                assert!(verify_proof(leaf_hash, &proof, root));
+            }
+        }
+        #[test]
+        fn proof_for_non_existing_leaf_fails() {
+            let values: Vec<&str> = vec!["a", "b", "c"];
+            let tree = MerkleTree::from_bytes(&values);
+            let fake_hash = sha256("z".as_bytes());
+            let proof = tree.generate_proof(&fake_hash);
+            assert!(proof.is_none());
+        }
+        #[test]
+        fn odd_number_of_leaves_padding_case() {
+            let values: Vec<&str> = vec!["a", "b", "c"];
+            let tree = MerkleTree::from_bytes(&values);
+            let leaf_hash = sha256("c".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let root = *tree.get_root().unwrap();
+            assert!(verify_proof(leaf_hash, &proof, root));
+        }
+        #[test]
+        fn single_element_has_empty_proof() {
+            let values: Vec<&str> = vec!["only"];
+            let tree = MerkleTree::from_bytes(&values);
+            let leaf_hash = sha256("only".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let root = *tree.get_root().unwrap();
+            assert!(proof.is_empty());
+            assert!(verify_proof(leaf_hash, &proof, root));
+        }
+        #[test]
+        fn empty_tree_has_no_root_and_no_proofs() {
+            let tree = MerkleTree::from_bytes::<&[u8]>(&[]);
+            assert!(tree.get_root().is_none());
+            assert!(tree.generate_proof(&[0u8; 32]).is_none());
+        }
+        #[test]
+        fn verify_proof_accepts_valid_single_step_proof() {
+            let leaf = sha256("a".as_bytes());
+            let sibling = sha256("b".as_bytes());
+            let mut data = Vec::new();
+            data.extend_from_slice(&leaf);
+            data.extend_from_slice(&sibling);
+            let root = sha256(&data);
+            let proof = vec![(sibling, Direction::Right)];
+            assert!(verify_proof(leaf, &proof, root));
+        }
+        #[test]
+        fn verify_proof_fails_if_sibling_hash_is_wrong() {
+            let leaf = sha256("a".as_bytes());
+            let correct_sibling = sha256("b".as_bytes());
+            let wrong_sibling = sha256("x".as_bytes());
+            let mut data = Vec::new();
+            data.extend_from_slice(&leaf);
+            data.extend_from_slice(&correct_sibling);
+            let root = sha256(&data);
+            let proof = vec![(wrong_sibling, Direction::Right)];
+            assert!(!verify_proof(leaf, &proof, root));
+        }
+        #[test]
+        fn verify_proof_fails_if_direction_is_wrong() {
+            let leaf = sha256("a".as_bytes());
+            let sibling = sha256("b".as_bytes());
+            let mut data = Vec::new();
+            data.extend_from_slice(&leaf);
+            data.extend_from_slice(&sibling);
+            let root = sha256(&data);
+            let proof = vec![(sibling, Direction::Left)];
+            assert!(!verify_proof(leaf, &proof, root));
+        }
+        #[test]
+        fn verify_proof_empty_is_valid_only_when_leaf_equals_root() {
+            let leaf = sha256("a".as_bytes());
+            let other = sha256("x".as_bytes());
+            assert!(verify_proof(leaf, &[], leaf));
+            assert!(!verify_proof(leaf, &[], other));
+        }
+    }
+    mod recovered_indices {
+        use super::*;
+        #[test]
+        fn every_leaf_of_8_leaf_tree_recovers_its_index() {
+            let values: Vec<[u8; 1]> = (0..8u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            for (index, value) in values.iter().enumerate() {
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert_eq!(
+                    verify_proof_with_index(sha256(value), &proof, root),
+                    Some(index)
+                );
+                let other = sha256(&values[(index + 1) % values.len()]);
+                assert_eq!(verify_proof_with_index(other, &proof, root), None);
+            }
+        }
+        #[test]
+        fn invalid_proof_reports_no_index() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(2).unwrap();
+            let result = verify_proof_with_index(sha256("a".as_bytes()), &proof, root);
+            assert_eq!(result, None);
+        }
+    }
+    mod proof_by_index {
+        use super::*;
+        #[test]
+        fn identical_leaves_get_their_own_proofs() {
+            let tree = MerkleTree::from_bytes(&["x", "a", "b", "x"]);
+            let root = *tree.get_root().unwrap();
+            let leaf = sha256("x".as_bytes());
+            let first = tree.generate_proof_by_index(0).unwrap();
+            let last = tree.generate_proof_by_index(3).unwrap();
+            assert_ne!(first, last);
+            assert!(verify_proof(leaf, &first, root));
+            assert!(verify_proof(leaf, &last, root));
+            assert_eq!(index_from_directions(&first), Some(0));
+            assert_eq!(index_from_directions(&last), Some(3));
+            assert_eq!(tree.generate_proof(&leaf), Some(first));
+        }
+        #[test]
+        fn out_of_range_index_has_no_proof() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert!(tree.generate_proof_by_index(3).is_none());
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert!(empty.generate_proof_by_index(0).is_none());
+        }
+    }
+    mod key_value_leaves {
+        use super::*;
+        fn pairs() -> Vec<(&'static [u8], &'static [u8])> {
+            return vec![(b"alice", b"10"), (b"bob", b"20"), (b"carol", b"30")];
+        }
+        #[test]
+        fn correct_pair_verifies() {
+            let tree = MerkleTree::from_kv_pairs(&pairs());
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            assert!(verify_kv_proof(b"bob", b"20", &proof, root));
+            assert_eq!(tree.leaves[1], sha256(b"\x03\0\0\0\0\0\0\0bob20"));
+        }
+        #[test]
+        fn different_splits_of_the_same_bytes_differ() {
+            assert_ne!(kv_leaf_hash(b"ab", b"c"), kv_leaf_hash(b"a", b"bc"));
+            let tree = MerkleTree::from_kv_pairs(&[(b"ab".as_slice(), b"c".as_slice())]);
+            let root = *tree.get_root().unwrap();
+            assert!(verify_kv_proof(b"ab", b"c", &[], root));
+            assert!(!verify_kv_proof(b"a", b"bc", &[], root));
+        }
+        #[test]
+        fn changed_value_or_key_fails() {
+            let tree = MerkleTree::from_kv_pairs(&pairs());
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            assert!(!verify_kv_proof(b"bob", b"21", &proof, root));
+            assert!(!verify_kv_proof(b"alice", b"20", &proof, root));
+        }
+    }
+    mod strict_values {
+        use super::*;
+        #[test]
+        fn value_matching_its_claimed_hash_verifies() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            assert!(verify_value_strict(b"b", sha256(b"b"), &proof, root));
+            assert!(!verify_value_strict(
+                b"b",
+                sha256(b"b"),
+                &proof,
+                sha256(b"x")
+            ));
+        }
+        #[test]
+        fn mismatched_claimed_hash_fails_despite_valid_proof() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            assert!(verify_proof(sha256(b"b"), &proof, root));
+            assert!(!verify_value_strict(b"spoofed", sha256(b"b"), &proof, root));
+        }
+    }
+    mod iterators {
+        use super::*;
+        #[test]
+        fn collect_matches_from_bytes() {
+            let items: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+            let tree: MerkleTree = items.clone().into_iter().collect();
+            assert_eq!(tree.get_root(), MerkleTree::from_bytes(&items).get_root());
+            let empty: MerkleTree = Vec::<String>::new().into_iter().collect();
+            assert_eq!(empty.get_root(), None);
+        }
+        #[test]
+        fn into_iter_yields_leaf_hashes() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let borrowed: Vec<&[u8; 32]> = (&tree).into_iter().collect();
+            assert_eq!(borrowed, vec![&sha256(b"a"), &sha256(b"b")]);
+            let mut count = 0;
+            for leaf in &tree {
+                assert!(tree.contains(leaf));
+                count += 1;
+            }
+            assert_eq!(count, 2);
+            let owned: Vec<[u8; 32]> = tree.into_iter().collect();
+            assert_eq!(owned, vec![sha256(b"a"), sha256(b"b")]);
+        }
+    }
+    mod incremental {
+        use super::*;
+        #[test]
+        fn push_matches_rebuild_at_every_size() {
+            let mut tree = MerkleTree::with_incremental();
+            assert_eq!(tree.get_root(), None);
+            let mut values = Vec::new();
+            for i in 1..=256u32 {
+                let value = i.to_le_bytes();
+                tree.push(&value);
+                values.push(value);
+                let rebuilt = MerkleTree::from_bytes(&values);
+                assert_eq!(tree.get_root(), rebuilt.get_root(), "size {i}");
+                let last = values.len() - 1;
+                assert_eq!(
+                    tree.generate_proof_by_index(last),
+                    rebuilt.generate_proof_by_index(last)
+                );
+            }
+        }
+        #[test]
+        fn proofs_and_updates_match_rebuilt_tree() {
+            let values: Vec<[u8; 4]> = (0..13u32).map(u32::to_le_bytes).collect();
+            for padding in [PaddingStrategy::Duplicate, PaddingStrategy::Promote] {
+                let mut tree = MerkleTree::with_incremental().with_padding_strategy(padding);
+                tree.extend(&values[..5]);
+                for value in &values[5..] {
+                    tree.push(value);
+                }
+                let mut rebuilt = MerkleTree::from_bytes_with_padding(&values, padding);
+                assert_eq!(tree, rebuilt);
+                for (index, value) in values.iter().enumerate() {
+                    let leaf = sha256(value);
+                    assert_eq!(tree.generate_proof(&leaf), rebuilt.generate_proof(&leaf));
+                    assert_eq!(
+                        tree.generate_proof_by_index(index),
+                        rebuilt.generate_proof_by_index(index)
+                    );
+                }
+                for index in [0, 6, 12] {
+                    tree.update(index, b"new").unwrap();
+                    rebuilt.update(index, b"new").unwrap();
+                    assert_eq!(tree, rebuilt);
+                }
+                #[cfg(feature = "std")]
+                assert_eq!(tree.to_dot(), rebuilt.to_dot());
+            }
+        }
+        #[test]
+        fn clear_and_rollback_keep_incremental_layers() {
+            let mut tree = MerkleTree::with_incremental();
+            tree.extend(&["a", "b", "c"]);
+            let checkpoint = tree.push_checkpoint(b"d");
+            assert!(tree.rollback(checkpoint));
+            assert_eq!(tree, MerkleTree::from_bytes(&["a", "b", "c"]));
+            tree.push(b"e");
+            assert_eq!(tree, MerkleTree::from_bytes(&["a", "b", "c", "e"]));
+            tree.clear();
+            assert_eq!(tree.get_root(), None);
+            tree.push(b"a");
+            assert_eq!(tree, MerkleTree::from_bytes(&["a"]));
+        }
+    }
+    mod versioned_roots {
+        use super::*;
+        #[test]
+        fn tagged_root_hashes_version_and_root() {
+            let (tree, tagged_root) = MerkleTree::from_bytes_versioned(&["a", "b", "c"], 7);
+            let mut data = 7u32.to_le_bytes().to_vec();
+            data.extend_from_slice(tree.get_root().unwrap());
+            assert_eq!(tagged_root, Some(sha256(&data)));
+            assert_eq!(tree, MerkleTree::from_bytes(&["a", "b", "c"]));
+            let (_, empty) = MerkleTree::from_bytes_versioned::<&str>(&[], 7);
+            assert_eq!(empty, None);
+        }
+        #[test]
+        fn proof_is_bound_to_its_version() {
+            let values = ["a", "b", "c"];
+            let (tree, v1_root) = MerkleTree::from_bytes_versioned(&values, 1);
+            let (_, v2_root) = MerkleTree::from_bytes_versioned(&values, 2);
+            let inner_root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            let leaf = sha256(b"b");
+            assert!(verify_proof_versioned(
+                leaf,
+                &proof,
+                inner_root,
+                1,
+                v1_root.unwrap()
+            ));
+            assert!(!verify_proof_versioned(
+                leaf,
+                &proof,
+                inner_root,
+                1,
+                v2_root.unwrap()
+            ));
+            assert!(!verify_proof_versioned(
+                leaf,
+                &proof,
+                inner_root,
+                2,
+                v1_root.unwrap()
+            ));
+            assert!(!verify_proof_versioned(
+                sha256(b"x"),
+                &proof,
+                inner_root,
+                1,
+                v1_root.unwrap()
+            ));
+        }
+    }
+    mod log_entries {
+        use super::*;
+        const ENTRIES: [&[u8]; 4] = [b"login", b"read", b"write", b"logout"];
+        #[test]
+        fn entries_verify_at_their_sequence_number() {
+            let tree = MerkleTree::from_log_entries(&ENTRIES);
+            let root = *tree.get_root().unwrap();
+            for (seq, entry) in ENTRIES.iter().enumerate() {
+                let proof = tree.generate_proof_by_index(seq).unwrap();
+                assert!(verify_log_entry(seq as u64, entry, &proof, root));
+                assert!(!verify_log_entry(seq as u64 + 1, entry, &proof, root));
+            }
+            let mut data = 2u64.to_le_bytes().to_vec();
+            data.extend_from_slice(b"write");
+            assert_eq!(tree.leaves[2], sha256(&data));
+        }
+        #[test]
+        fn reordering_entries_invalidates_their_proofs() {
+            let tree = MerkleTree::from_log_entries(&ENTRIES);
+            let root = *tree.get_root().unwrap();
+            let reordered = [ENTRIES[1], ENTRIES[0], ENTRIES[2], ENTRIES[3]];
+            let swapped = MerkleTree::from_log_entries(&reordered);
+            assert_ne!(swapped.get_root(), tree.get_root());
+            for (seq, entry) in reordered.iter().enumerate().take(2) {
+                let proof = swapped.generate_proof_by_index(seq).unwrap();
+                assert!(!verify_log_entry(seq as u64, entry, &proof, root));
+            }
+            let plain = MerkleTree::from_bytes(&reordered);
+            assert_ne!(
+                plain.get_root(),
+                MerkleTree::from_bytes(&ENTRIES).get_root()
+            );
+        }
+    }
+    mod chunked_leaves {
+        use super::*;
+        fn chunks(values: &[&str]) -> Vec<Vec<u8>> {
+            return values.iter().map(|v| v.as_bytes().to_vec()).collect();
+        }
+        #[test]
+        fn subtree_leaf_hash_is_root_of_its_chunks() {
+            let values = vec![
+                chunks(&["a", "b", "c"]),
+                chunks(&["d"]),
+                chunks(&["e", "f"]),
+            ];
+            let tree = MerkleTree::from_chunked_values(&values);
+            for (leaf, value) in tree.leaves.iter().zip(&values) {
+                assert_eq!(Some(leaf), MerkleTree::from_bytes(value).get_root());
+            }
+            let expected = MerkleTree::from_leaf_hashes_vec(tree.leaves.clone());
+            assert_eq!(tree.get_root(), expected.get_root());
+        }
+        #[test]
+        fn empty_chunk_list_differs_from_single_empty_chunk() {
+            let empty = MerkleTree::from_chunked_values(&[vec![]]);
+            let single = MerkleTree::from_chunked_values(&[vec![vec![]]]);
+            assert_eq!(empty.leaves, vec![[0u8; 32]]);
+            assert_eq!(single.leaves, vec![sha256(&[])]);
+            assert_ne!(empty.get_root(), single.get_root());
+        }
+        #[test]
+        fn chunk_leaf_proof_chains_into_tree_proof() {
+            let values = vec![chunks(&["a", "b", "c"]), chunks(&["d", "e"])];
+            let tree = MerkleTree::from_chunked_values(&values);
+            let chunk_tree = MerkleTree::from_bytes(&values[1]);
+            let mut proof = chunk_tree.generate_proof_by_index(0).unwrap();
+            proof.extend(tree.generate_proof_by_index(1).unwrap());
+            let root = *tree.get_root().unwrap();
+            assert!(verify_proof(sha256("d".as_bytes()), &proof, root));
+        }
+    }
+    mod numeric_leaves {
+        use super::*;
+        #[test]
+        fn u64_leaves_match_encoded_bytes() {
+            let values = [1u64, 2, 3];
+            let big: Vec<[u8; 8]> = values.iter().map(|v| v.to_be_bytes()).collect();
+            let little: Vec<[u8; 8]> = values.iter().map(|v| v.to_le_bytes()).collect();
+            assert_eq!(
+                MerkleTree::from_u64s(&values, Endian::Big).get_root(),
+                MerkleTree::from_bytes(&big).get_root()
+            );
+            assert_eq!(
+                MerkleTree::from_u64s(&values, Endian::Little).get_root(),
+                MerkleTree::from_bytes(&little).get_root()
+            );
+        }
+        #[test]
+        fn endianness_changes_root() {
+            let values = [1u64, 2, 3, 4];
+            let big = MerkleTree::from_u64s(&values, Endian::Big);
+            let little = MerkleTree::from_u64s(&values, Endian::Little);
+            assert_ne!(big.get_root(), little.get_root());
+        }
+        #[test]
+        fn u64_proof_verifies_only_with_matching_endianness() {
+            let values = [10u64, 20, 30];
+            let tree = MerkleTree::from_u64s(&values, Endian::Little);
+            let root = *tree.get_root().unwrap();
+            let proof = tree
+                .generate_proof(&leaf_hash_u64(20, Endian::Little))
+                .unwrap();
+            assert!(verify_proof_u64(20, Endian::Little, &proof, root));
+            assert!(!verify_proof_u64(20, Endian::Big, &proof, root));
+            assert!(!verify_proof_u64(30, Endian::Little, &proof, root));
+        }
+    }
+    mod commitments {
+        use super::*;
+        fn sealed_bids() -> Vec<(&'static [u8], [u8; 32])> {
+            return vec![
+                (b"alice:100", [1u8; 32]),
+                (b"bob:250", [2u8; 32]),
+                (b"carol:175", [3u8; 32]),
+            ];
+        }
+        #[test]
+        fn correct_reveal_verifies() {
+            let bids = sealed_bids();
+            let entries: Vec<(&[u8], &[u8; 32])> = bids.iter().map(|(v, n)| (*v, n)).collect();
+            let tree = MerkleTree::from_commitments(&entries);
+            let root = *tree.get_root().unwrap();
+            let proof = tree
+                .generate_proof(&commitment(b"bob:250", &[2u8; 32]))
+                .unwrap();
+            assert!(verify_reveal(b"bob:250", &[2u8; 32], &proof, root));
+        }
+        #[test]
+        fn wrong_nonce_or_value_fails() {
+            let bids = sealed_bids();
+            let entries: Vec<(&[u8], &[u8; 32])> = bids.iter().map(|(v, n)| (*v, n)).collect();
+            let tree = MerkleTree::from_commitments(&entries);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            assert!(!verify_reveal(b"bob:250", &[9u8; 32], &proof, root));
+            assert!(!verify_reveal(b"bob:999", &[2u8; 32], &proof, root));
+        }
+        #[test]
+        fn leaves_are_blinded() {
+            let tree = MerkleTree::from_commitments(&[(b"alice:100", &[1u8; 32])]);
+            assert_ne!(tree.get_root(), Some(&sha256(b"alice:100")));
+        }
+    }
+    mod statistics {
+        use super::*;
+        #[test]
+        fn five_leaf_tree_stats() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            assert_eq!(
+                tree.stats(),
+                TreeStats {
+                    leaf_count: 5,
+                    depth: 4,
+                    internal_node_count: 6,
+                    is_perfect: false,
+                    padding_count: 2,
+                }
+            );
+        }
+        #[test]
+        fn perfect_tree_has_no_padding() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let stats = tree.stats();
+            assert!(stats.is_perfect);
+            assert_eq!(stats.padding_count, 0);
+            assert_eq!(stats.internal_node_count, 3);
+            assert_eq!(stats.depth, 3);
+        }
+        #[test]
+        fn empty_and_single_leaf_stats() {
+            let empty = MerkleTree::from_bytes::<&[u8]>(&[]).stats();
+            assert_eq!(empty.leaf_count, 0);
+            assert_eq!(empty.depth, 0);
+            assert!(!empty.is_perfect);
+            let single = MerkleTree::from_bytes(&["x"]).stats();
+            assert_eq!(single.depth, 1);
+            assert_eq!(single.internal_node_count, 0);
+            assert!(single.is_perfect);
+        }
+        #[test]
+        fn fixed_depth_stats_count_zero_subtrees() {
+            let tree = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c", "d", "e"], 4).unwrap();
+            assert_eq!(
+                tree.stats(),
+                TreeStats {
+                    leaf_count: 5,
+                    depth: 5,
+                    internal_node_count: 7,
+                    is_perfect: false,
+                    padding_count: 3,
+                }
+            );
+            assert_eq!(tree.stats().depth, tree.depth());
+            let full = MerkleTree::from_bytes_fixed_depth(&["a", "b"], 1).unwrap();
+            assert!(full.stats().is_perfect);
+            let empty = MerkleTree::from_bytes_fixed_depth::<&str>(&[], 2)
+                .unwrap()
+                .stats();
+            assert_eq!((empty.depth, empty.internal_node_count), (3, 1));
+        }
+    }
+    mod level_commitments {
+        use super::*;
+        #[test]
+        fn level_zero_commits_to_all_leaves() {
+            let values = vec!["a", "b", "c"];
+            let tree = MerkleTree::from_bytes(&values);
+            let mut concatenated = Vec::new();
+            for value in &values {
+                concatenated.extend_from_slice(&sha256(value.as_bytes()));
+            }
+            assert_eq!(tree.level_commitment(0), Some(sha256(&concatenated)));
+        }
+        #[test]
+        fn level_nodes_follow_tree_structure() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaves = tree.level_nodes(0).unwrap();
+            let parents = tree.level_nodes(1).unwrap();
+            assert_eq!(leaves.len(), 3);
+            assert_eq!(parents[0], hash_pair(&leaves[0], &leaves[1]));
+            assert_eq!(parents[1], hash_pair(&leaves[2], &leaves[2]));
+            assert_eq!(tree.level_nodes(2), Some(vec![*tree.get_root().unwrap()]));
+            assert_eq!(
+                tree.level_commitment(2),
+                Some(sha256(tree.get_root().unwrap()))
+            );
+        }
+        #[test]
+        fn levels_above_root_are_none() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert!(tree.level_nodes(3).is_none());
+            assert!(tree.level_commitment(3).is_none());
+            let empty = MerkleTree::from_bytes::<&[u8]>(&[]);
+            assert!(empty.level_commitment(0).is_none());
+        }
+    }
+    #[cfg(feature = "std")]
+    mod ascii_art {
+        use super::*;
+        #[test]
+        fn four_leaf_tree_shows_root_and_leaf_prefixes() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let art = tree.to_ascii_art();
+            let root = hex::encode(tree.get_root().unwrap());
+            assert!(art.starts_with(&format!("root {}\n", &root[..8])));
+            for (index, value) in ["a", "b", "c", "d"].iter().enumerate() {
+                let leaf = hex::encode(&sha256(value.as_bytes()));
+                assert!(art.contains(&format!("leaf {} {}", index, &leaf[..8])));
+            }
+            assert_eq!(art.lines().count(), 7);
+            assert_eq!(art, tree.to_ascii_art());
+        }
+        #[test]
+        fn padding_is_drawn() {
+            let art = MerkleTree::from_bytes(&["a", "b", "c"]).to_ascii_art();
+            let c = &hex::encode(&sha256(b"c"))[..8];
+            assert_eq!(art.matches(&format!("leaf 2 {}", c)).count(), 2);
+            let promoted =
+                MerkleTree::from_bytes_with_padding(&["a", "b", "c"], PaddingStrategy::Promote);
+            let art = promoted.to_ascii_art();
+            assert_eq!(art.matches(&format!("leaf 2 {}", c)).count(), 1);
+        }
+        #[test]
+        fn large_and_empty_trees_get_a_summary() {
+            let values: Vec<[u8; 1]> = (0..17u8).map(|i| [i]).collect();
+            let art = MerkleTree::from_bytes(&values).to_ascii_art();
+            assert_eq!(art.lines().count(), 1);
+            assert!(art.starts_with("tree of 17 leaves"));
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(empty.to_ascii_art(), "(empty tree)\n");
+        }
+    }
+    #[cfg(feature = "std")]
+    mod dot_export {
+        use super::*;
+        #[test]
+        fn four_leaf_tree_has_seven_nodes_and_six_edges() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let dot = tree.to_dot();
+            assert!(dot.starts_with("digraph merkle {\n"));
+            assert!(dot.ends_with("}\n"));
+            assert_eq!(dot.matches("[label=").count(), 13);
+            assert_eq!(dot.matches(" -> ").count(), 6);
+            assert_eq!(dot.matches("[label=\"L\"]").count(), 3);
+            assert_eq!(dot.matches("[label=\"R\"]").count(), 3);
+            let root = hex::encode(tree.get_root().unwrap());
+            assert!(dot.contains(&format!("n0 [label=\"{}\"];", &root[..8])));
+        }
+        #[test]
+        fn empty_tree_is_an_empty_graph() {
+            let tree = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(tree.to_dot(), "digraph merkle {\n}\n");
+        }
+    }
+    #[cfg(feature = "std")]
+    mod csv_export {
+        use super::*;
+        #[test]
+        fn csv_has_one_row_per_leaf_and_root_comment() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let csv = tree.to_csv();
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(lines[0], "index,leaf_hash_hex");
+            let rows: Vec<&&str> = lines
+                .iter()
+                .filter(|l| !l.starts_with('#'))
+                .skip(1)
+                .collect();
+            assert_eq!(rows.len(), tree.leaves.len());
+            assert_eq!(
+                *rows[1],
+                format!("1,{}", hex::encode(&sha256("b".as_bytes())))
+            );
+            assert_eq!(
+                *lines.last().unwrap(),
+                format!("# root,{}", hex::encode(tree.get_root().unwrap()))
+            );
+        }
+        #[test]
+        fn empty_tree_csv_is_only_header() {
+            let tree = MerkleTree::from_bytes::<&[u8]>(&[]);
+            assert_eq!(tree.to_csv(), "index,leaf_hash_hex\n");
+        }
+    }
+    #[cfg(feature = "serde")]
+    mod json_export {
+        use super::*;
+        #[test]
+        fn json_contains_leaf_count_and_root() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let json: serde_json::Value = serde_json::from_str(&tree.to_json()).unwrap();
+            assert_eq!(json["leaf_count"], 3);
+            assert_eq!(json["root"], hex::encode(tree.get_root().unwrap()));
+            assert_eq!(json["leaves"][1], hex::encode(&sha256("b".as_bytes())));
+            assert_eq!(json["leaves"].as_array().unwrap().len(), 3);
+        }
+        #[test]
+        fn empty_tree_has_null_root() {
+            let tree = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(
+                tree.to_json(),
+                r#"{"root":null,"leaf_count":0,"leaves":[]}"#
+            );
+        }
+    }
+    mod fingerprint {
+        use super::*;
+        #[test]
+        fn identical_trees_share_fingerprint() {
+            let t1 = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let t2 = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert_eq!(t1.fingerprint(), t2.fingerprint());
+        }
+        #[test]
+        fn modified_tree_changes_fingerprint() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let before = tree.fingerprint();
+            tree.push("d".as_bytes());
+            assert_ne!(before, tree.fingerprint());
+            let other = MerkleTree::from_bytes(&["a", "b", "x"]);
+            assert_ne!(before, other.fingerprint());
+        }
+        #[test]
+        fn fingerprint_is_truncated_root_and_count_hash() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let mut data = tree.get_root().unwrap().to_vec();
+            data.extend_from_slice(&2u64.to_le_bytes());
+            let digest = sha256(&data);
+            assert_eq!(
+                tree.fingerprint(),
+                u64::from_le_bytes(digest[..8].try_into().unwrap())
+            );
+        }
+    }
+    #[cfg(feature = "std")]
+    mod receipts {
+        use super::*;
+        use std::time::SystemTime;
+        #[test]
+        fn successful_verification_returns_receipt() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let leaf_hash = sha256("a".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let before = SystemTime::now();
+            let receipt = verify_with_receipt(leaf_hash, &proof, root).unwrap();
+            assert_eq!(receipt.leaf, leaf_hash);
+            assert_eq!(receipt.root, root);
+            assert_eq!(receipt.steps, 2);
+            assert!(receipt.verified_at >= before);
+        }
+        #[test]
+        fn tampered_proof_returns_none() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let leaf_hash = sha256("a".as_bytes());
+            let mut proof = tree.generate_proof(&leaf_hash).unwrap();
+            proof[1].0[0] ^= 1;
+            assert!(verify_with_receipt(leaf_hash, &proof, root).is_none());
+        }
+    }
+    mod root_from_leaves {
+        use super::*;
+        #[test]
+        fn correct_leaf_set_reconstructs_root() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let root = *tree.get_root().unwrap();
+            assert!(verify_root_from_leaves(&tree.leaves, root));
+        }
+        #[test]
+        fn flipped_byte_fails() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let root = *tree.get_root().unwrap();
+            let mut leaves = tree.leaves.clone();
+            leaves[3][0] ^= 1;
+            assert!(!verify_root_from_leaves(&leaves, root));
+        }
+        #[test]
+        fn empty_leaf_set_never_matches() {
+            assert!(!verify_root_from_leaves(&[], sha256(&[])));
+        }
+    }
+    mod slice_verification {
+        use super::*;
+        #[test]
+        fn slices_with_correct_length_are_verified() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaf_hash = sha256("b".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let root = tree.get_root().unwrap().to_vec();
+            assert_eq!(verify_proof_slice(&leaf_hash, &proof, &root), Ok(true));
+            let other = sha256("z".as_bytes());
+            assert_eq!(verify_proof_slice(&other, &proof, &root), Ok(false));
+        }
+        #[test]
+        fn short_leaf_is_rejected() {
+            let root = sha256("a".as_bytes());
+            assert_eq!(
+                verify_proof_slice(&[1, 2, 3], &[], &root),
+                Err(LengthError::Leaf { len: 3 })
+            );
+        }
+        #[test]
+        fn short_root_is_rejected() {
+            let leaf_hash = sha256("a".as_bytes());
+            assert_eq!(
+                verify_proof_slice(&leaf_hash, &[], &leaf_hash[..31]),
+                Err(LengthError::Root { len: 31 })
+            );
+        }
+    }
+    mod optional_siblings {
+        use super::*;
+        #[test]
+        fn missing_padding_sibling_is_duplicated() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let leaf_hash = sha256("c".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let steps = vec![(None, Direction::Right), (Some(proof[1].0), proof[1].1)];
+            assert!(verify_proof_optional_siblings(leaf_hash, &steps, root));
+        }
+        #[test]
+        fn explicit_siblings_behave_like_verify_proof() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let leaf_hash = sha256("b".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let steps: Vec<_> = proof.iter().map(|(h, d)| (Some(*h), *d)).collect();
+            assert!(verify_proof_optional_siblings(leaf_hash, &steps, root));
+            let mut wrong = steps.clone();
+            wrong[0].0 = None;
+            assert!(!verify_proof_optional_siblings(leaf_hash, &wrong, root));
+        }
+    }
+    mod hash_counting {
+        use super::*;
+        #[test]
+        fn hash_count_equals_proof_length() {
+            for size in 1..=16u8 {
+                let values: Vec<[u8; 1]> = (0..size).map(|i| [i]).collect();
+                let tree = MerkleTree::from_bytes(&values);
+                let root = *tree.get_root().unwrap();
+                let proof = tree.generate_proof_by_index(0).unwrap();
+                let (valid, hashes) = verify_proof_counting(sha256(&values[0]), &proof, root);
+                assert!(valid);
+                assert_eq!(hashes, proof.len());
+            }
+        }
+        #[test]
+        fn invalid_proof_still_reports_count() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(0).unwrap();
+            let result = verify_proof_counting(sha256("z".as_bytes()), &proof, root);
+            assert_eq!(result, (false, 2));
+        }
+    }
+    mod strict_verification {
+        use super::*;
+        #[test]
+        fn root_differing_only_in_last_byte_is_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let leaf = sha256("c".as_bytes());
+            let proof = tree.generate_proof_by_index(2).unwrap();
+            let root = *tree.get_root().unwrap();
+            assert!(verify_proof_strict(leaf, &proof, root));
+            let mut almost = root;
+            almost[31] ^= 1;
+            assert!(!verify_proof_strict(leaf, &proof, almost));
+        }
+    }
+    mod canonical_verification {
+        use super::*;
+        #[test]
+        fn canonical_padding_step_is_accepted() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaf_hash = sha256("c".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let root = *tree.get_root().unwrap();
+            assert_eq!(proof[0], (leaf_hash, Direction::Right));
+            assert!(verify_proof_canonical(leaf_hash, &proof, root, 3));
+        }
+        #[test]
+        fn self_sibling_with_wrong_direction_is_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaf_hash = sha256("c".as_bytes());
+            let mut proof = tree.generate_proof(&leaf_hash).unwrap();
+            let root = *tree.get_root().unwrap();
+            proof[0].1 = Direction::Left;
+            assert!(verify_proof(leaf_hash, &proof, root));
+            assert!(!verify_proof_canonical(leaf_hash, &proof, root, 3));
+        }
+        #[test]
+        fn equal_adjacent_leaves_are_accepted() {
+            let tree = MerkleTree::from_bytes(&["a", "a"]);
+            let leaf_hash = sha256("a".as_bytes());
+            let proof = tree.generate_proof_by_index(1).unwrap();
+            let root = *tree.get_root().unwrap();
+            assert_eq!(proof, vec![(leaf_hash, Direction::Left)]);
+            assert!(verify_proof_canonical(leaf_hash, &proof, root, 2));
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "c"]);
+            let proof = tree.generate_proof_by_index(3).unwrap();
+            let root = *tree.get_root().unwrap();
+            assert!(verify_proof_canonical(
+                sha256("c".as_bytes()),
+                &proof,
+                root,
+                4
+            ));
+        }
+        #[test]
+        fn canonical_verification_accepts_regular_proofs() {
+            let data = vec!["a", "b", "c", "d"];
+            let tree = MerkleTree::from_bytes(&data);
+            let root = *tree.get_root().unwrap();
+            for value in &data {
+                let leaf_hash = sha256(value.as_bytes());
+                let proof = tree.generate_proof(&leaf_hash).unwrap();
+                assert!(verify_proof_canonical(leaf_hash, &proof, root, data.len()));
+            }
+        }
+    }
+    mod partial_verification {
+        use super::*;
+        #[test]
+        fn folding_all_steps_gives_root() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let leaf_hash = sha256("c".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let root = verify_proof_prefix_steps(leaf_hash, &proof, proof.len()).unwrap();
+            assert_eq!(&root, tree.get_root().unwrap());
+        }
+        #[test]
+        fn folding_can_continue_from_partial_root() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let leaf_hash = sha256("d".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let partial = verify_proof_prefix_steps(leaf_hash, &proof, 1).unwrap();
+            let rest = &proof[1..];
+            assert!(verify_proof(partial, rest, *tree.get_root().unwrap()));
+        }
+        #[test]
+        fn folding_zero_steps_returns_leaf() {
+            let leaf_hash = sha256("a".as_bytes());
+            let proof = vec![(sha256("b".as_bytes()), Direction::Right)];
+            assert_eq!(
+                verify_proof_prefix_steps(leaf_hash, &proof, 0),
+                Some(leaf_hash)
+            );
+        }
+        #[test]
+        fn fold_iter_yields_each_intermediate_hash() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let leaf_hash = sha256("c".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let hashes: Vec<[u8; 32]> = proof_fold_iter(leaf_hash, &proof).collect();
+            assert_eq!(hashes.len(), proof.len());
+            for (k, hash) in hashes.iter().enumerate() {
+                assert_eq!(
+                    Some(*hash),
+                    verify_proof_prefix_steps(leaf_hash, &proof, k + 1)
+                );
+            }
+            assert_eq!(
+                hashes.last().copied(),
+                verify_proof_prefix_steps(leaf_hash, &proof, proof.len())
+            );
+            assert_eq!(hashes.last(), tree.get_root());
+        }
+        #[test]
+        fn fold_iter_of_empty_proof_yields_nothing() {
+            assert_eq!(proof_fold_iter(sha256(b"a"), &[]).count(), 0);
+        }
+        #[test]
+        fn folding_more_steps_than_proof_returns_none() {
+            let leaf_hash = sha256("a".as_bytes());
+            assert_eq!(verify_proof_prefix_steps(leaf_hash, &[], 1), None);
+            let proof = vec![(sha256("b".as_bytes()), Direction::Right)];
+            assert_eq!(verify_proof_prefix_steps(leaf_hash, &proof, 2), None);
+            assert_eq!(
+                verify_proof_prefix_steps(leaf_hash, &proof, usize::MAX),
+                None
+            );
+        }
+    }
+    mod index_form {
+        use super::*;
+        #[test]
+        fn proofs_survive_round_trip_and_still_verify() {
+            let values = ["a", "b", "c", "d", "e", "f"];
+            let tree = MerkleTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            for (position, value) in values.iter().enumerate() {
+                let leaf = sha256(value.as_bytes());
+                let proof = tree.generate_proof(&leaf).unwrap();
+                let (index, siblings) = proof_to_index_form(&proof).unwrap();
+                assert_eq!(index, position);
+                let restored = index_form_to_proof(index, &siblings, values.len()).unwrap();
+                assert_eq!(restored, proof);
+                assert!(verify_proof(leaf, &restored, root));
+            }
+        }
+        #[test]
+        fn mismatched_index_forms_are_rejected() {
+            let siblings = [[1u8; 32], [2u8; 32]];
+            assert!(index_form_to_proof(4, &siblings, 4).is_none());
+            assert!(index_form_to_proof(0, &siblings, 5).is_none());
+            assert!(index_form_to_proof(0, &siblings[..1], 4).is_none());
+        }
+    }
+    mod proof_size {
+        use super::*;
+        #[test]
+        fn two_step_proof_corresponds_to_three_or_four_leaves() {
+            assert_eq!(proof_size_bounds(2), (3, 4));
+        }
+        #[test]
+        fn bounds_match_actual_proof_lengths() {
+            for size in 1..=17u8 {
+                let values: Vec<[u8; 1]> = (0..size).map(|i| [i]).collect();
+                let tree = MerkleTree::from_bytes(&values);
+                let proof = tree.generate_proof_by_index(0).unwrap();
+                let (min, max) = proof_size_bounds(proof.len());
+                assert!(min <= values.len() && values.len() <= max);
+            }
+        }
+        #[test]
+        fn small_and_huge_proof_lengths() {
+            assert_eq!(proof_size_bounds(0), (1, 1));
+            assert_eq!(proof_size_bounds(1), (2, 2));
+            assert_eq!(proof_size_bounds(200), (usize::MAX, usize::MAX));
+        }
+    }
+    mod filtered_roots {
+        use super::*;
+        #[test]
+        fn even_indices_match_tree_of_kept_leaves() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let leaf0 = sha256("a".as_bytes());
+            let leaf2 = sha256("c".as_bytes());
+            let expected = MerkleTree::from_leaf_hashes_vec(vec![leaf0, leaf2]);
+            let root = tree.filtered_root(|index, _| index % 2 == 0);
+            assert_eq!(root.as_ref(), expected.get_root());
+        }
+        #[test]
+        fn filtering_by_hash_and_filtering_everything() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let leaf_b = sha256("b".as_bytes());
+            assert_eq!(tree.filtered_root(|_, hash| *hash == leaf_b), Some(leaf_b));
+            assert_eq!(tree.filtered_root(|_, _| true).as_ref(), tree.get_root());
+            assert_eq!(tree.filtered_root(|_, _| false), None);
+        }
+    }
+    mod root_verifiers {
+        use super::*;
+        #[test]
+        fn verifier_checks_several_proofs_against_its_root() {
+            let values = ["a", "b", "c", "d", "e"];
+            let tree = MerkleTree::from_bytes(&values);
+            let verify = MerkleTree::verifier_for(*tree.get_root().unwrap());
+            for (index, value) in values.iter().enumerate() {
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert!(verify(&sha256(value.as_bytes()), &proof));
+            }
+            let proof = tree.generate_proof_by_index(0).unwrap();
+            assert!(!verify(&sha256("b".as_bytes()), &proof));
+            let other = MerkleTree::verifier_for(sha256(&[]));
+            assert!(!other(&sha256("a".as_bytes()), &proof));
+        }
+    }
+    mod common_prefix {
+        use super::*;
+        #[test]
+        fn diverging_logs_share_first_three_leaves() {
+            let a = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let b = MerkleTree::from_bytes(&["a", "b", "c", "x", "e"]);
+            assert_eq!(common_prefix_len(&a, &b), 3);
+        }
+        #[test]
+        fn prefix_is_bounded_by_shorter_tree() {
+            let a = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let b = MerkleTree::from_bytes(&["a", "b"]);
+            assert_eq!(common_prefix_len(&a, &b), 2);
+            assert_eq!(common_prefix_len(&b, &a), 2);
+            assert_eq!(common_prefix_len(&a, &MerkleTree::from_bytes(&["z"])), 0);
+        }
+    }
+    mod sibling_digests {
+        use super::*;
+        #[test]
+        fn identical_proofs_share_a_digest() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let first = tree.generate_proof_by_index(1).unwrap();
+            let second = tree.generate_proof(&sha256("b".as_bytes())).unwrap();
+            assert_eq!(sibling_digest(&first), sibling_digest(&second));
+        }
+        #[test]
+        fn differing_proofs_have_different_digests() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let proof = tree.generate_proof_by_index(0).unwrap();
+            let other = tree.generate_proof_by_index(2).unwrap();
+            assert_ne!(sibling_digest(&proof), sibling_digest(&other));
+            let mut flipped = proof.clone();
+            flipped[0].1 = Direction::Left;
+            assert_ne!(sibling_digest(&proof), sibling_digest(&flipped));
+        }
+    }
+    mod boolean_proofs {
+        use super::*;
+        #[test]
+        fn booleans_match_directions() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            for index in 0..5 {
+                let bools = tree.generate_proof_bools(index).unwrap();
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert_eq!(bools.len(), proof.len());
+                for ((bool_sibling, is_right), (sibling, direction)) in bools.iter().zip(&proof) {
+                    assert_eq!(bool_sibling, sibling);
+                    assert_eq!(*is_right, *direction == Direction::Right);
+                }
+            }
+            assert!(tree.generate_proof_bools(5).is_none());
+        }
+    }
+    mod auth_paths {
+        use super::*;
+        #[test]
+        fn auth_path_splits_back_into_siblings() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            for index in 0..5 {
+                let bytes = tree.auth_path_bytes(index).unwrap();
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert_eq!(bytes.len(), proof.len() * 32);
+                for (chunk, (sibling, _)) in bytes.chunks_exact(32).zip(&proof) {
+                    assert_eq!(chunk, sibling);
+                }
+            }
+            assert!(tree.auth_path_bytes(5).is_none());
+        }
+    }
+    mod annotated_ranges {
+        use super::*;
+        #[test]
+        fn last_sibling_of_leaf_0_covers_right_half() {
+            let values = ["a", "b", "c", "d", "e", "f", "g", "h"];
+            let tree = MerkleTree::from_bytes(&values);
+            let steps = tree.generate_proof_annotated_ranges(0).unwrap();
+            let covers: Vec<(usize, usize)> = steps.iter().map(|step| step.covers).collect();
+            assert_eq!(covers, vec![(1, 2), (2, 4), (4, 8)]);
+            assert_eq!(steps[2].direction, Direction::Right);
+            let right_half = MerkleTree::from_bytes(&values[4..]);
+            assert_eq!(Some(&steps[2].sibling), right_half.get_root());
+        }
+        #[test]
+        fn ranges_are_clipped_for_padded_levels() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let steps = tree.generate_proof_annotated_ranges(4).unwrap();
+            let covers: Vec<(usize, usize)> = steps.iter().map(|step| step.covers).collect();
+            assert_eq!(covers, vec![(4, 5), (4, 5), (0, 4)]);
+            let proof = tree.generate_proof_by_index(4).unwrap();
+            for (step, (sibling, direction)) in steps.iter().zip(&proof) {
+                assert_eq!((step.sibling, step.direction), (*sibling, *direction));
+            }
+            assert!(tree.generate_proof_annotated_ranges(5).is_none());
+        }
+        #[test]
+        fn empty_subtrees_of_fixed_depth_trees_cover_no_leaves() {
+            let tree = MerkleTree::from_bytes_fixed_depth(&["a", "b", "c", "d", "e"], 4).unwrap();
+            let steps = tree.generate_proof_annotated_ranges(4).unwrap();
+            let covers: Vec<(usize, usize)> = steps.iter().map(|step| step.covers).collect();
+            assert_eq!(covers, vec![(5, 5), (5, 5), (0, 4), (5, 5)]);
+            let proof = tree.generate_proof_by_index(4).unwrap();
+            for (step, (sibling, direction)) in steps.iter().zip(&proof) {
+                assert_eq!((step.sibling, step.direction), (*sibling, *direction));
+            }
+        }
+    }
+    mod storage_estimates {
+        use super::*;
+        #[test]
+        fn estimate_matches_encoded_proofs_of_8_leaf_tree() {
+            let values: Vec<[u8; 1]> = (0..8u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let actual: usize = tree.proofs_iter().map(|proof| proof.len() * 33).sum();
+            assert_eq!(proof_storage_estimate(8), actual);
+            assert_eq!(actual, 8 * 3 * 33);
+        }
+        #[test]
+        fn estimate_for_padded_and_trivial_trees() {
+            let values: Vec<[u8; 1]> = (0..5u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let actual: usize = tree.proofs_iter().map(|proof| proof.len() * 33).sum();
+            assert_eq!(proof_storage_estimate(5), actual);
+            assert_eq!(proof_storage_estimate(1), 0);
+            assert_eq!(proof_storage_estimate(0), 0);
+        }
+    }
+    mod bitpath_proofs {
+        use super::*;
+        #[test]
+        fn bitpath_encodes_same_directions_as_proof() {
+            let values: Vec<[u8; 1]> = (0..7u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            for (index, value) in values.iter().enumerate() {
+                let proof = tree.generate_proof(&sha256(value)).unwrap();
+                let (bitpath, siblings) = tree.generate_bitpath_proof(index).unwrap();
+                assert_eq!(siblings.len(), proof.len());
+                for (level, (sibling_hash, direction)) in proof.iter().enumerate() {
+                    let is_right = (bitpath >> level) & 1 == 1;
+                    assert_eq!(is_right, *direction == Direction::Left);
+                    assert_eq!(siblings[level], *sibling_hash);
+                }
+            }
+        }
+        #[test]
+        fn bitpath_proofs_verify() {
+            let values: Vec<[u8; 1]> = (0..5u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            for (index, value) in values.iter().enumerate() {
+                let (bitpath, siblings) = tree.generate_bitpath_proof(index).unwrap();
+                assert_eq!(bitpath, index as u64);
+                assert!(verify_bitpath_proof(
+                    sha256(value),
+                    bitpath,
+                    &siblings,
+                    root
+                ));
+            }
+        }
+        #[test]
+        fn wrong_or_non_canonical_bitpath_fails() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let (bitpath, siblings) = tree.generate_bitpath_proof(1).unwrap();
+            let leaf_hash = sha256("b".as_bytes());
+            assert!(!verify_bitpath_proof(
+                leaf_hash,
+                bitpath ^ 1,
+                &siblings,
+                root
+            ));
+            assert!(!verify_bitpath_proof(
+                leaf_hash,
+                bitpath | 1 << 2,
+                &siblings,
+                root
+            ));
+            assert!(tree.generate_bitpath_proof(4).is_none());
+        }
+        #[test]
+        fn proofs_deeper_than_bitpath_limit_are_rejected() {
+            let siblings = vec![[0u8; 32]; MAX_BITPATH_DEPTH + 1];
+            assert!(!verify_bitpath_proof([0u8; 32], 0, &siblings, [0u8; 32]));
+        }
+        #[test]
+        fn max_leaves_for_depth_is_power_of_two() {
+            assert_eq!(max_leaves_for_depth(0), 1);
+            assert_eq!(max_leaves_for_depth(3), 8);
+            assert_eq!(
+                max_leaves_for_depth(usize::BITS as usize - 1),
+                1 << (usize::BITS - 1)
+            );
+            assert_eq!(max_leaves_for_depth(MAX_BITPATH_DEPTH), usize::MAX);
+        }
+    }
+    mod minimal_proofs {
+        use super::*;
+        #[test]
+        fn plausible_sizes_follow_index_and_length() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(0).unwrap();
+            assert_eq!(proof.len(), 2);
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"a"), &proof, root, 10),
+                vec![3, 4]
+            );
+            let proof = tree.generate_proof_by_index(3).unwrap();
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"d"), &proof, root, 10),
+                vec![4]
+            );
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"d"), &proof, root, 3),
+                Vec::<usize>::new()
+            );
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"x"), &proof, root, 10),
+                Vec::<usize>::new()
+            );
+        }
+        #[test]
+        fn plausible_sizes_stop_at_the_proof_length() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(0).unwrap();
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"a"), &proof, root, usize::MAX),
+                vec![3, 4]
+            );
+            let tree = MerkleTree::from_bytes(&["a"]);
+            let root = *tree.get_root().unwrap();
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"a"), &[], root, usize::MAX),
+                vec![1]
+            );
+        }
+        #[test]
+        fn plausible_sizes_require_duplicated_padding() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c", "d"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(2).unwrap();
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"c"), &proof, root, 10),
+                vec![4]
+            );
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof_by_index(2).unwrap();
+            assert_eq!(
+                plausible_tree_sizes(sha256(b"c"), &proof, root, 10),
+                vec![3, 4]
+            );
+        }
+        #[test]
+        fn expected_length_for_small_trees() {
+            assert_eq!(expected_proof_len(1, 0), Some(0));
+            assert_eq!(expected_proof_len(2, 1), Some(1));
+            assert_eq!(expected_proof_len(5, 4), Some(3));
+            assert_eq!(expected_proof_len(8, 7), Some(3));
+            assert_eq!(expected_proof_len(8, 8), None);
+        }
+        #[test]
+        fn generated_proofs_are_minimal() {
+            let values: Vec<[u8; 1]> = (0..6u8).map(|i| [i]).collect();
+            let tree = MerkleTree::from_bytes(&values);
+            let root = *tree.get_root().unwrap();
+            for (index, value) in values.iter().enumerate() {
+                let proof = tree.generate_proof_by_index(index).unwrap();
+                assert!(verify_proof_minimal(
+                    sha256(value),
+                    &proof,
+                    root,
+                    values.len()
+                ));
             }
-            return true;
+        }
+        #[test]
+        fn padded_proof_is_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b"]);
+            let leaf_hash = sha256("a".as_bytes());
+            let mut proof = tree.generate_proof(&leaf_hash).unwrap();
+            let extra = sha256("padding".as_bytes());
+            proof.push((extra, Direction::Right));
+            let contrived_root = hash_pair(tree.get_root().unwrap(), &extra);
+            assert!(verify_proof(leaf_hash, &proof, contrived_root));
+            assert!(!verify_proof_minimal(leaf_hash, &proof, contrived_root, 2));
+        }
+        #[test]
+        fn index_beyond_leaf_count_is_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = *tree.get_root().unwrap();
+            let leaf_hash = sha256("c".as_bytes());
+            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            assert!(verify_proof_minimal(leaf_hash, &proof, root, 3));
+            assert!(!verify_proof_minimal(leaf_hash, &proof, root, 2));
         }
     }
-    if let Some(right) = &node.right {
-        if dfs_generate_proof(right, target, proof) {
-            if let Some(left) = &node.left {
-                proof.push((left.hash, Direction::Left));
+    #[cfg(feature = "std")]
+    mod membership_index {
+        use super::*;
+        #[test]
+        fn index_agrees_with_linear_scan() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "a", "c", "b"]);
+            let index = tree.build_membership_index();
+            assert_eq!(index.len(), 3);
+            for value in ["a", "b", "c", "d", ""] {
+                let hash = sha256(value.as_bytes());
+                assert_eq!(index.contains(&hash), tree.leaves.contains(&hash));
             }
-            return true;
+        }
+        #[test]
+        fn empty_tree_has_empty_index() {
+            let index = MerkleTree::from_bytes::<&str>(&[]).build_membership_index();
+            assert!(index.is_empty());
+            assert!(!index.contains(&sha256(b"a")));
         }
     }
-    return false;
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    mod tree_construction {
+    mod into_verifier {
         use super::*;
         #[test]
-        fn merkle_tree_is_deterministic() {
-            let data = vec!["a", "b", "c", "d"];
-            let t1 = MerkleTree::from_bytes(&data);
-            let t2 = MerkleTree::from_bytes(&data);
-            assert_eq!(t1.get_root(), t2.get_root());
+        fn verifier_accepts_only_included_values() {
+            let verify = MerkleTree::from_bytes(&["c", "a", "b", "a"]).into_verifier();
+            for value in ["a", "b", "c"] {
+                assert!(verify(value.as_bytes()));
+            }
+            for value in ["d", "", "ab"] {
+                assert!(!verify(value.as_bytes()));
+            }
+        }
+        struct DoubleSha256;
+        impl Hasher for DoubleSha256 {
+            fn digest(&self, data: &[u8]) -> [u8; 32] {
+                return sha256(&sha256(data));
+            }
         }
         #[test]
-        fn empty_input_has_no_root() {
-            let data: Vec<&[u8]> = vec![];
-            let tree = MerkleTree::from_bytes(&data);
-            assert!(tree.root.is_none());
+        fn verifier_hashes_values_with_the_tree_hasher() {
+            let verify = MerkleTree::from_bytes_with(&["a", "b"], DoubleSha256).into_verifier();
+            assert!(verify(b"a"));
+            assert!(!verify(&sha256(b"a")));
+            let verify = MerkleTree::from_bytes::<&str>(&[]).into_verifier();
+            assert!(!verify(b"a"));
+        }
+    }
+    mod leaf_slice {
+        use super::*;
+        #[test]
+        fn index_of_returns_lowest_position() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "a", "c", "b"]);
+            assert_eq!(tree.index_of(&sha256(b"a")), Some(0));
+            assert_eq!(tree.index_of(&sha256(b"b")), Some(1));
+            assert_eq!(tree.index_of(&sha256(b"c")), Some(3));
+            assert_eq!(tree.index_of(&sha256(b"d")), None);
+            let leaf = sha256(b"c");
+            let index = tree.index_of(&leaf).unwrap();
+            assert_eq!(
+                tree.generate_proof_by_index(index),
+                tree.generate_proof(&leaf)
+            );
         }
         #[test]
-        fn single_element_tree() {
-            let data: Vec<&[u8]> = vec![&[42u8]];
-            let tree = MerkleTree::from_bytes(&data);
-            let expected = sha256(&[42u8]);
-            assert_eq!(tree.get_root(), Some(&expected));
+        fn contains_finds_present_leaves_only() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert!(tree.contains(&sha256(b"a")));
+            assert!(tree.contains(&sha256(b"c")));
+            assert!(!tree.contains(&sha256(b"d")));
+            tree.push(b"d");
+            assert!(tree.contains(&sha256(b"d")));
+            assert!(!tree.contains(&tree.get_root().copied().unwrap()));
+            assert!(!MerkleTree::from_bytes::<&str>(&[]).contains(&sha256(b"a")));
         }
         #[test]
-        fn multiple_elements_tree_has_root() {
-            let data: Vec<&[u8]> = vec![&[1], &[2], &[3], &[4]];
-            let tree = MerkleTree::from_bytes(&data);
-            assert!(tree.root.is_some());
+        fn leaves_borrow_the_leaf_hashes_in_order() {
+            let values = ["a", "b", "c"];
+            let tree = MerkleTree::from_bytes(&values);
+            let leaves = tree.leaves();
+            assert_eq!(leaves.len(), tree.len());
+            for (leaf, value) in leaves.iter().zip(values) {
+                assert_eq!(*leaf, sha256(value.as_bytes()));
+            }
+            assert!(MerkleTree::from_bytes::<&str>(&[]).leaves().is_empty());
         }
         #[test]
-        fn odd_number_of_elements_is_supported() {
-            let data: Vec<&[u8]> = vec![&[1], &[2], &[3]];
-            let tree = MerkleTree::from_bytes(&data);
-            assert!(tree.root.is_some());
+        fn iter_leaves_and_get_leaf_follow_tree_order() {
+            let values = ["a", "b", "c", "d", "e"];
+            let tree = MerkleTree::from_bytes(&values);
+            let expected: Vec<[u8; 32]> = values.iter().map(|v| sha256(v.as_bytes())).collect();
+            assert!(tree.iter_leaves().eq(expected.iter()));
+            for (index, leaf) in expected.iter().enumerate() {
+                assert_eq!(tree.get_leaf(index), Some(leaf));
+            }
+            assert_eq!(tree.get_leaf(5), None);
         }
     }
-    mod proof_generation_and_verification {
+    mod descriptive_errors {
         use super::*;
         #[test]
-        fn changing_inputs_changes_root() {
-            let base = MerkleTree::from_bytes(&["a", "b", "c"]);
-            assert_ne!(
-                base.get_root(),
-                MerkleTree::from_bytes(&["c", "b", "a"]).get_root()
-            );
-            assert_ne!(
-                base.get_root(),
-                MerkleTree::from_bytes(&["a", "b", "d"]).get_root()
+        fn proof_errors_distinguish_empty_tree_from_missing_leaf() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let proof = tree.try_generate_proof(&sha256(b"b")).unwrap();
+            assert_eq!(Some(proof), tree.generate_proof(&sha256(b"b")));
+            assert_eq!(
+                tree.try_generate_proof(&sha256(b"z")),
+                Err(MerkleError::LeafNotFound)
             );
-            assert_ne!(
-                base.get_root(),
-                MerkleTree::from_bytes(&["a", "b"]).get_root()
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(
+                empty.try_generate_proof(&sha256(b"a")),
+                Err(MerkleError::EmptyTree)
             );
         }
         #[test]
-        fn generate_and_verify_proof_for_each_leaf() {
-            let data = vec!["a", "b", "c", "d"];
-            let tree = MerkleTree::from_bytes(&data);
-            let root = *tree.get_root().unwrap();
-            for value in &data {
-                let leaf_hash = sha256(value.as_bytes());
-                let proof = tree.generate_proof(&leaf_hash).unwrap();
-                assert!(verify_proof(leaf_hash, &proof, root));
-            }
+        fn leaf_errors_report_index_and_len() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert_eq!(tree.try_get_leaf(2), Ok(sha256(b"c")));
+            assert_eq!(
+                tree.try_get_leaf(3),
+                Err(MerkleError::IndexOutOfRange { index: 3, len: 3 })
+            );
+            let empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(empty.try_get_leaf(0), Err(MerkleError::EmptyTree));
         }
+    }
+    mod proof_cache {
+        use super::*;
         #[test]
-        fn proof_for_non_existing_leaf_fails() {
-            let values: Vec<&str> = vec!["a", "b", "c"];
-            let tree = MerkleTree::from_bytes(&values);
-            let fake_hash = sha256("z".as_bytes());
-            let proof = tree.generate_proof(&fake_hash);
-            assert!(proof.is_none());
+        fn cached_proofs_match_proofs_by_index() {
+            for size in 1..=9u8 {
+                let values: Vec<[u8; 1]> = (0..size).map(|i| [i]).collect();
+                let tree = MerkleTree::from_bytes(&values);
+                let cache = tree.build_proof_cache();
+                for index in 0..values.len() {
+                    assert_eq!(cache.proof(index), tree.generate_proof_by_index(index));
+                }
+            }
         }
         #[test]
-        fn odd_number_of_leaves_padding_case() {
-            let values: Vec<&str> = vec!["a", "b", "c"];
+        fn cached_proofs_verify_against_root() {
+            let values = vec!["a", "b", "c", "d", "e"];
             let tree = MerkleTree::from_bytes(&values);
-            let leaf_hash = sha256("c".as_bytes());
-            let proof = tree.generate_proof(&leaf_hash).unwrap();
+            let cache = tree.build_proof_cache();
             let root = *tree.get_root().unwrap();
-            assert!(verify_proof(leaf_hash, &proof, root));
+            for (index, value) in values.iter().enumerate() {
+                let proof = cache.proof(index).unwrap();
+                assert!(verify_proof(sha256(value.as_bytes()), &proof, root));
+            }
         }
         #[test]
-        fn single_element_has_empty_proof() {
-            let values: Vec<&str> = vec!["only"];
+        fn proofs_iter_yields_one_proof_per_leaf() {
+            let values: Vec<[u8; 1]> = (0..6u8).map(|i| [i]).collect();
             let tree = MerkleTree::from_bytes(&values);
-            let leaf_hash = sha256("only".as_bytes());
-            let proof = tree.generate_proof(&leaf_hash).unwrap();
-            let root = *tree.get_root().unwrap();
-            assert!(proof.is_empty());
-            assert!(verify_proof(leaf_hash, &proof, root));
+            let proofs: Vec<_> = tree.proofs_iter().collect();
+            assert_eq!(proofs.len(), tree.leaves.len());
+            for (index, proof) in proofs.iter().enumerate() {
+                assert_eq!(Some(proof), tree.generate_proof_by_index(index).as_ref());
+            }
         }
         #[test]
-        fn empty_tree_has_no_root_and_no_proofs() {
+        fn proofs_iter_is_empty_for_empty_tree() {
             let tree = MerkleTree::from_bytes::<&[u8]>(&[]);
-            assert!(tree.get_root().is_none());
-            assert!(tree.generate_proof(&[0u8; 32]).is_none());
+            assert_eq!(tree.proofs_iter().count(), 0);
         }
         #[test]
-        fn verify_proof_accepts_valid_single_step_proof() {
-            let leaf = sha256("a".as_bytes());
-            let sibling = sha256("b".as_bytes());
-            let mut data = Vec::new();
-            data.extend_from_slice(&leaf);
-            data.extend_from_slice(&sibling);
-            let root = sha256(&data);
-            let proof = vec![(sibling, Direction::Right)];
-            assert!(verify_proof(leaf, &proof, root));
+        fn cache_returns_none_out_of_range() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert!(tree.build_proof_cache().proof(3).is_none());
+            let empty = MerkleTree::from_bytes::<&[u8]>(&[]);
+            assert!(empty.build_proof_cache().proof(0).is_none());
         }
+    }
+    mod in_place_updates {
+        use super::*;
         #[test]
-        fn verify_proof_fails_if_sibling_hash_is_wrong() {
-            let leaf = sha256("a".as_bytes());
-            let correct_sibling = sha256("b".as_bytes());
-            let wrong_sibling = sha256("x".as_bytes());
-            let mut data = Vec::new();
-            data.extend_from_slice(&leaf);
-            data.extend_from_slice(&correct_sibling);
-            let root = sha256(&data);
-            let proof = vec![(wrong_sibling, Direction::Right)];
-            assert!(!verify_proof(leaf, &proof, root));
+        fn update_and_diff_returns_roots_and_new_proof() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            let old_root = *tree.get_root().unwrap();
+            let ((old, new), proof) = tree.update_and_diff(2, b"z").unwrap();
+            assert_eq!(old, old_root);
+            assert_eq!(&new, tree.get_root().unwrap());
+            assert_ne!(old, new);
+            assert!(verify_proof(sha256(b"z"), &proof, new));
+            assert!(!verify_proof(sha256(b"c"), &proof, new));
         }
         #[test]
-        fn verify_proof_fails_if_direction_is_wrong() {
-            let leaf = sha256("a".as_bytes());
-            let sibling = sha256("b".as_bytes());
-            let mut data = Vec::new();
-            data.extend_from_slice(&leaf);
-            data.extend_from_slice(&sibling);
-            let root = sha256(&data);
-            let proof = vec![(sibling, Direction::Left)];
-            assert!(!verify_proof(leaf, &proof, root));
+        fn update_and_diff_out_of_range_is_none() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b"]);
+            assert_eq!(tree.update_and_diff(2, b"z"), None);
+            assert_eq!(
+                MerkleTree::from_bytes::<&str>(&[]).update_and_diff(0, b"z"),
+                None
+            );
+            assert_eq!(tree, MerkleTree::from_bytes(&["a", "b"]));
         }
         #[test]
-        fn verify_proof_empty_is_valid_only_when_leaf_equals_root() {
-            let leaf = sha256("a".as_bytes());
-            let other = sha256("x".as_bytes());
-            assert!(verify_proof(leaf, &[], leaf));
-            assert!(!verify_proof(leaf, &[], other));
+        fn updated_root_matches_fresh_tree() {
+            for size in 1..=9u8 {
+                let mut values: Vec<[u8; 1]> = (0..size).map(|i| [i]).collect();
+                let mut tree = MerkleTree::from_bytes(&values);
+                for index in 0..values.len() {
+                    values[index] = [100 + index as u8];
+                    tree.update(index, &values[index]).unwrap();
+                    let fresh = MerkleTree::from_bytes(&values);
+                    assert_eq!(tree.get_root(), fresh.get_root());
+                }
+            }
+        }
+        #[test]
+        fn proofs_follow_updated_leaf() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b", "c", "d", "e"]);
+            tree.update(4, "z".as_bytes()).unwrap();
+            let leaf = sha256("z".as_bytes());
+            let root = *tree.get_root().unwrap();
+            let proof = tree.generate_proof(&leaf).unwrap();
+            assert_eq!(tree.generate_proof_by_index(4), Some(proof.clone()));
+            assert!(verify_proof(leaf, &proof, root));
+            assert!(tree.generate_proof(&sha256("e".as_bytes())).is_none());
+        }
+        #[test]
+        fn out_of_range_index_is_an_error() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b"]);
+            let root = *tree.get_root().unwrap();
+            assert_eq!(
+                tree.update(2, "c".as_bytes()),
+                Err(IndexError { index: 2, len: 2 })
+            );
+            assert_eq!(tree.get_root(), Some(&root));
+            let mut empty = MerkleTree::from_bytes::<&str>(&[]);
+            assert_eq!(
+                empty.update(0, "a".as_bytes()),
+                Err(IndexError { index: 0, len: 0 })
+            );
         }
     }
     mod tree_dinamic_update {
         use super::*;
         #[test]
+        fn extend_matches_tree_built_at_once() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b"]);
+            tree.extend(&["c", "d"]);
+            assert_eq!(
+                tree.get_root(),
+                MerkleTree::from_bytes(&["a", "b", "c", "d"]).get_root()
+            );
+            let mut pushed = MerkleTree::from_bytes(&["a", "b"]);
+            pushed.push("c".as_bytes());
+            pushed.push("d".as_bytes());
+            assert_eq!(tree.get_root(), pushed.get_root());
+        }
+        #[test]
+        fn extend_empty_tree_and_with_nothing() {
+            let mut tree = MerkleTree::from_bytes::<&str>(&[]);
+            tree.extend(&["a", "b", "c"]);
+            let expected = MerkleTree::from_bytes(&["a", "b", "c"]);
+            assert_eq!(tree.get_root(), expected.get_root());
+            tree.extend::<&str>(&[]);
+            assert_eq!(tree.get_root(), expected.get_root());
+        }
+        #[test]
         fn push_produces_same_root_as_from_bytes() {
             let values = vec!["a", "b", "c", "d"];
             let direct_tree = MerkleTree::from_bytes(&values);
@@ -310,6 +3760,68 @@ mod tests {
             assert_eq!(direct_tree.get_root(), pushed_tree.get_root());
         }
         #[test]
+        fn clear_empties_tree_and_keeps_capacity() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let capacity = tree.leaves.capacity();
+            tree.clear();
+            assert!(tree.leaves.is_empty());
+            assert!(tree.get_root().is_none());
+            assert_eq!(tree.leaves.capacity(), capacity);
+        }
+        #[test]
+        fn push_after_clear_rebuilds_correctly() {
+            let mut tree = MerkleTree::from_bytes(&["x", "y", "z"]);
+            tree.clear();
+            tree.push("a".as_bytes());
+            tree.push("b".as_bytes());
+            assert_eq!(
+                tree.get_root(),
+                MerkleTree::from_bytes(&["a", "b"]).get_root()
+            );
+        }
+        #[test]
+        fn rollback_restores_intermediate_state() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b"]);
+            tree.push_checkpoint("c".as_bytes());
+            let intermediate = *tree.get_root().unwrap();
+            let checkpoint = tree.push_checkpoint("d".as_bytes());
+            assert_ne!(tree.get_root(), Some(&intermediate));
+            assert!(tree.rollback(checkpoint));
+            assert_eq!(tree.get_root(), Some(&intermediate));
+            assert_eq!(
+                tree.get_root(),
+                MerkleTree::from_bytes(&["a", "b", "c"]).get_root()
+            );
+        }
+        #[test]
+        fn stale_checkpoints_are_rejected() {
+            let mut tree = MerkleTree::from_bytes(&["a", "b"]);
+            let first = tree.push_checkpoint(b"c");
+            let second = tree.push_checkpoint(b"d");
+            assert!(tree.rollback(first));
+            let current = MerkleTree::from_bytes(&["a", "b"]);
+            assert!(!tree.rollback(second));
+            assert_eq!(tree, current);
+            assert!(tree.rollback(first));
+            let checkpoint = tree.push_checkpoint(b"c");
+            tree.update(0, b"z").unwrap();
+            let updated = MerkleTree::from_bytes(&["z", "b", "c"]);
+            assert!(!tree.rollback(checkpoint));
+            assert_eq!(tree, updated);
+            let checkpoint = tree.push_checkpoint(b"d");
+            tree.clear();
+            assert!(!tree.rollback(checkpoint));
+            assert!(tree.is_empty());
+        }
+        #[test]
+        fn rollback_to_empty_tree() {
+            let mut tree = MerkleTree::from_bytes::<&[u8]>(&[]);
+            let checkpoint = tree.push_checkpoint("a".as_bytes());
+            assert!(tree.rollback(checkpoint));
+            assert!(tree.get_root().is_none());
+            assert!(tree.leaves.is_empty());
+        }
+        #[test]
         fn push_changes_root_and_invalidates_old_proof() {
             let mut tree = MerkleTree::from_bytes(&["a", "b"]);
             let leaf_hash = sha256("a".as_bytes());
@@ -321,4 +3833,220 @@ mod tests {
             assert!(!verify_proof(leaf_hash, &proof, new_root));
         }
     }
+    mod padding_strategy {
+        use super::*;
+        const VALUES: [&str; 3] = ["a", "b", "c"];
+        #[test]
+        fn autodetect_recognises_promoted_proofs() {
+            let tree = MerkleTree::from_bytes_with_padding(&VALUES, PaddingStrategy::Promote);
+            let root = *tree.get_root().unwrap();
+            let leaf = sha256(b"c");
+            let proof = tree.generate_proof_by_index(2).unwrap();
+            assert!(!verify_proof_minimal(leaf, &proof, root, 3));
+            assert_eq!(
+                verify_proof_autodetect(leaf, &proof, root, 3),
+                Some(PaddingStrategy::Promote)
+            );
+            assert_eq!(verify_proof_autodetect(sha256(b"z"), &proof, root, 3), None);
+        }
+        #[test]
+        fn autodetect_accepts_proofs_of_both_strategies() {
+            for count in 1..=9 {
+                let values: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+                for padding in [PaddingStrategy::Duplicate, PaddingStrategy::Promote] {
+                    let tree = MerkleTree::from_bytes_with_padding(&values, padding);
+                    let root = *tree.get_root().unwrap();
+                    for (index, value) in values.iter().enumerate() {
+                        let proof = tree.generate_proof_by_index(index).unwrap();
+                        let leaf = sha256(value.as_bytes());
+                        let detected = verify_proof_autodetect(leaf, &proof, root, count);
+                        assert!(detected.is_some());
+                        if padding == PaddingStrategy::Duplicate {
+                            assert_eq!(detected, Some(PaddingStrategy::Duplicate));
+                        }
+                        assert_eq!(index_for_padding(&proof, count, padding), Some(index));
+                    }
+                }
+            }
+        }
+        #[test]
+        fn strategies_give_different_roots_for_three_leaves() {
+            let duplicate = MerkleTree::from_bytes(&VALUES);
+            let promote = MerkleTree::from_bytes_with_padding(&VALUES, PaddingStrategy::Promote);
+            let (a, b, c) = (sha256(b"a"), sha256(b"b"), sha256(b"c"));
+            let ab = hash_pair(&a, &b);
+            assert_eq!(
+                duplicate.get_root(),
+                Some(&hash_pair(&ab, &hash_pair(&c, &c)))
+            );
+            assert_eq!(promote.get_root(), Some(&hash_pair(&ab, &c)));
+            assert_eq!(
+                MerkleTree::from_bytes_with_padding(&VALUES, PaddingStrategy::Duplicate),
+                duplicate
+            );
+            assert_eq!(duplicate.padding_strategy(), PaddingStrategy::Duplicate);
+            assert_eq!(promote.padding_strategy(), PaddingStrategy::Promote);
+        }
+        #[test]
+        fn both_strategies_produce_verifiable_proofs() {
+            for count in 1..=9 {
+                let values: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+                for padding in [PaddingStrategy::Duplicate, PaddingStrategy::Promote] {
+                    let tree = MerkleTree::from_bytes_with_padding(&values, padding);
+                    let root = *tree.get_root().unwrap();
+                    let cache = tree.build_proof_cache();
+                    for (index, value) in values.iter().enumerate() {
+                        let leaf = sha256(value.as_bytes());
+                        let by_index = tree.generate_proof_by_index(index).unwrap();
+                        assert!(verify_proof(leaf, &by_index, root));
+                        assert_eq!(tree.generate_proof(&leaf), Some(by_index.clone()));
+                        assert_eq!(cache.proof(index), Some(by_index));
+                    }
+                }
+            }
+        }
+        #[test]
+        fn promoted_leaf_has_a_shorter_proof() {
+            let tree = MerkleTree::from_bytes_with_padding(&VALUES, PaddingStrategy::Promote);
+            let ab = hash_pair(&sha256(b"a"), &sha256(b"b"));
+            assert_eq!(
+                tree.generate_proof_by_index(2),
+                Some(vec![(ab, Direction::Left)])
+            );
+            let ranges = tree.generate_proof_annotated_ranges(2).unwrap();
+            assert_eq!(ranges.len(), 1);
+            assert_eq!(ranges[0].covers, (0, 2));
+        }
+        #[test]
+        fn mutations_keep_the_strategy() {
+            let mut tree =
+                MerkleTree::from_bytes_with_padding(&["a", "b"], PaddingStrategy::Promote);
+            tree.push(b"c");
+            assert_eq!(
+                tree,
+                MerkleTree::from_bytes_with_padding(&VALUES, PaddingStrategy::Promote)
+            );
+            tree.update(2, b"z").unwrap();
+            assert_eq!(
+                tree,
+                MerkleTree::from_bytes_with_padding(&["a", "b", "z"], PaddingStrategy::Promote)
+            );
+        }
+        #[test]
+        fn promotion_adds_no_padding_nodes() {
+            let stats = MerkleTree::from_bytes_with_padding(
+                &["a", "b", "c", "d", "e"],
+                PaddingStrategy::Promote,
+            )
+            .stats();
+            assert_eq!(stats.padding_count, 0);
+            assert_eq!(stats.internal_node_count, 4);
+            assert_eq!(stats.depth, 4);
+        }
+    }
+    #[cfg(feature = "std")]
+    mod from_lines {
+        use super::*;
+        use std::io::Cursor;
+        #[test]
+        fn matches_from_bytes_of_split_lines() {
+            let text = "first entry\nsecond entry\n\nfourth entry";
+            let tree = MerkleTree::from_lines(Cursor::new(text)).unwrap();
+            let lines: Vec<&str> = text.split('\n').collect();
+            assert_eq!(tree, MerkleTree::from_bytes(&lines));
+            assert_eq!(tree.len(), 4);
+        }
+        #[test]
+        fn trailing_newline_does_not_add_a_leaf() {
+            let with = MerkleTree::from_lines(Cursor::new("a\nb\nc\n")).unwrap();
+            let without = MerkleTree::from_lines(Cursor::new("a\nb\nc")).unwrap();
+            let crlf = MerkleTree::from_lines(Cursor::new("a\r\nb\r\nc\r\n")).unwrap();
+            assert_eq!(with, without);
+            assert_eq!(with, crlf);
+            assert_eq!(with, MerkleTree::from_bytes(&["a", "b", "c"]));
+        }
+        #[test]
+        fn empty_input_gives_empty_tree() {
+            let tree = MerkleTree::from_lines(Cursor::new("")).unwrap();
+            assert!(tree.is_empty());
+        }
+    }
+    #[cfg(feature = "std")]
+    mod hex_steps {
+        use super::*;
+        fn hex_proof(tree: &MerkleTree, index: usize) -> Vec<(String, Direction)> {
+            return tree
+                .generate_proof_by_index(index)
+                .unwrap()
+                .iter()
+                .map(|(sibling, direction)| (hex::encode(sibling), *direction))
+                .collect();
+        }
+        #[test]
+        fn valid_hex_proof_verifies() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = hex::encode(tree.get_root().unwrap());
+            let steps = hex_proof(&tree, 1);
+            let leaf = hex::encode(&sha256(b"b"));
+            assert_eq!(verify_proof_hex_steps(&leaf, &steps, &root), Ok(true));
+            let upper = leaf.to_uppercase();
+            assert_eq!(verify_proof_hex_steps(&upper, &steps, &root), Ok(true));
+            let other = hex::encode(&sha256(b"c"));
+            assert_eq!(verify_proof_hex_steps(&other, &steps, &root), Ok(false));
+        }
+        #[test]
+        fn bad_hex_digit_is_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = hex::encode(tree.get_root().unwrap());
+            let mut steps = hex_proof(&tree, 1);
+            steps[1].0.replace_range(5..6, "g");
+            let leaf = hex::encode(&sha256(b"b"));
+            assert_eq!(
+                verify_proof_hex_steps(&leaf, &steps, &root),
+                Err(HexError::InvalidDigit { index: 5 })
+            );
+        }
+        #[test]
+        fn wrong_length_sibling_is_rejected() {
+            let tree = MerkleTree::from_bytes(&["a", "b", "c"]);
+            let root = hex::encode(tree.get_root().unwrap());
+            let mut steps = hex_proof(&tree, 1);
+            steps[0].0.truncate(62);
+            let leaf = hex::encode(&sha256(b"b"));
+            assert_eq!(
+                verify_proof_hex_steps(&leaf, &steps, &root),
+                Err(HexError::InvalidLength { len: 62 })
+            );
+            assert_eq!(
+                verify_proof_hex_steps("", &[], &root),
+                Err(HexError::InvalidLength { len: 0 })
+            );
+        }
+    }
+    #[cfg(feature = "rayon")]
+    mod parallel_build {
+        use super::*;
+        #[test]
+        fn parallel_build_matches_serial_build() {
+            let values: Vec<[u8; 8]> = (0..100_000u64).map(|i| i.to_le_bytes()).collect();
+            let serial = MerkleTree::from_bytes(&values);
+            let parallel = MerkleTree::from_bytes_par(&values);
+            assert_eq!(parallel.get_root(), serial.get_root());
+            assert_eq!(parallel, serial);
+        }
+        #[test]
+        fn small_and_odd_sizes_match() {
+            for count in 0..=17u8 {
+                let values: Vec<[u8; 1]> = (0..count).map(|i| [i]).collect();
+                let parallel = MerkleTree::from_bytes_par(&values);
+                assert_eq!(parallel, MerkleTree::from_bytes(&values));
+                for (index, value) in values.iter().enumerate() {
+                    assert_eq!(
+                        parallel.generate_proof(&sha256(value)),
+                        parallel.generate_proof_by_index(index)
+                    );
+                }
+            }
+        }
+    }
 }
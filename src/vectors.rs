@@ -0,0 +1,68 @@
+use crate::tree::{Direction, MerkleTree};
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestVector {
+    pub leaves: Vec<Vec<u8>>,
+    pub root: [u8; 32],
+    pub proofs: Vec<Vec<([u8; 32], Direction)>>,
+}
+
+pub fn generate_test_vectors(seed: u64, count: usize) -> Vec<TestVector> {
+    let mut rng = SplitMix64 { state: seed };
+    let mut vectors = Vec::with_capacity(count);
+    for _ in 0..count {
+        let leaf_count = (rng.next() % 16 + 1) as usize;
+        let leaves: Vec<Vec<u8>> = (0..leaf_count)
+            .map(|_| {
+                let len = (rng.next() % 33) as usize;
+                (0..len).map(|_| rng.next() as u8).collect()
+            })
+            .collect();
+        let tree = MerkleTree::from_bytes(&leaves);
+        vectors.push(TestVector {
+            root: *tree.get_root().expect("vectors always have leaves"),
+            proofs: tree.proofs_iter().collect(),
+            leaves,
+        });
+    }
+    return vectors;
+}
+
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha256;
+    use crate::tree::verify_proof;
+    mod test_vectors {
+        use super::*;
+        #[test]
+        fn same_seed_yields_identical_vectors() {
+            assert_eq!(generate_test_vectors(7, 10), generate_test_vectors(7, 10));
+            assert_ne!(generate_test_vectors(7, 10), generate_test_vectors(8, 10));
+        }
+        #[test]
+        fn every_proof_verifies() {
+            for vector in generate_test_vectors(42, 20) {
+                assert_eq!(vector.proofs.len(), vector.leaves.len());
+                for (leaf, proof) in vector.leaves.iter().zip(&vector.proofs) {
+                    assert!(verify_proof(sha256(leaf), proof, vector.root));
+                }
+            }
+        }
+    }
+}